@@ -0,0 +1,123 @@
+//! Compares the bulk `enqueue_slice` / `dequeue_into` operations against element-wise loops, and
+//! power of two sized ring buffers (masked indices) against other sizes (indices wrapped with a
+//! comparison)
+//!
+//! Run with `cargo bench --bench ring_buffer`
+//!
+//! On x86_64 the time stamp counter is used to also report cycle counts.
+
+extern crate heapless;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use heapless::RingBuffer;
+
+const ROUNDS: usize = 100_000;
+const CHUNK: usize = 100;
+
+fn bench<F>(name: &str, mut f: F)
+where
+    F: FnMut(),
+{
+    // warm up
+    for _ in 0..ROUNDS / 10 {
+        f();
+    }
+
+    let start = Instant::now();
+    let start_cycles = cycles();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    let elapsed_cycles = cycles().map(|end| end - start_cycles.unwrap());
+    let elapsed = start.elapsed();
+
+    print!(
+        "{:<40} {:>8.1} ns/chunk",
+        name,
+        nanos(elapsed) / ROUNDS as f64,
+    );
+    if let Some(c) = elapsed_cycles {
+        print!(" {:>8.1} cycles/chunk", c as f64 / ROUNDS as f64);
+    }
+    println!(" ({} bytes)", CHUNK);
+}
+
+#[cfg(target_arch = "x86_64")]
+fn cycles() -> Option<u64> {
+    Some(unsafe { std::arch::x86_64::_rdtsc() })
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn cycles() -> Option<u64> {
+    None
+}
+
+fn nanos(d: Duration) -> f64 {
+    d.as_secs() as f64 * 1e9 + f64::from(d.subsec_nanos())
+}
+
+fn element_wise<const N: usize>(name: &str, input: &[u8; CHUNK], output: &mut [u8; CHUNK]) {
+    let mut rb: RingBuffer<u8, N> = RingBuffer::new();
+
+    bench(name, || {
+        for &byte in black_box(&input[..]) {
+            rb.enqueue(byte).unwrap();
+        }
+        for byte in output.iter_mut() {
+            *byte = rb.dequeue().unwrap();
+        }
+        black_box(&output);
+    });
+}
+
+fn bulk<const N: usize>(name: &str, input: &[u8; CHUNK], output: &mut [u8; CHUNK]) {
+    let mut rb: RingBuffer<u8, N> = RingBuffer::new();
+
+    bench(name, || {
+        assert_eq!(rb.enqueue_slice(black_box(&input[..])), CHUNK);
+        assert_eq!(rb.dequeue_into(output), CHUNK);
+        black_box(&output);
+    });
+}
+
+fn spsc_element_wise<const N: usize>(name: &str, input: &[u8; CHUNK], output: &mut [u8; CHUNK]) {
+    let mut rb: RingBuffer<u8, N> = RingBuffer::new();
+    let (mut p, mut c) = rb.split();
+
+    bench(name, || {
+        for &byte in black_box(&input[..]) {
+            p.enqueue(byte).unwrap();
+        }
+        for byte in output.iter_mut() {
+            *byte = c.dequeue().unwrap();
+        }
+        black_box(&output);
+    });
+}
+
+fn spsc_bulk<const N: usize>(name: &str, input: &[u8; CHUNK], output: &mut [u8; CHUNK]) {
+    let mut rb: RingBuffer<u8, N> = RingBuffer::new();
+    let (mut p, mut c) = rb.split();
+
+    bench(name, || {
+        assert_eq!(p.enqueue_slice(black_box(&input[..])), CHUNK);
+        assert_eq!(c.dequeue_into(output), CHUNK);
+        black_box(&output);
+    });
+}
+
+fn main() {
+    let input = [0xaa; CHUNK];
+    let mut output = [0; CHUNK];
+
+    element_wise::<256>("RingBuffer element-wise (mask)", &input, &mut output);
+    element_wise::<257>("RingBuffer element-wise (compare)", &input, &mut output);
+    bulk::<256>("RingBuffer bulk (mask)", &input, &mut output);
+    bulk::<257>("RingBuffer bulk (compare)", &input, &mut output);
+    spsc_element_wise::<256>("Producer/Consumer element-wise (mask)", &input, &mut output);
+    spsc_element_wise::<257>("Producer/Consumer element-wise (compare)", &input, &mut output);
+    spsc_bulk::<256>("Producer/Consumer bulk (mask)", &input, &mut output);
+    spsc_bulk::<257>("Producer/Consumer bulk (compare)", &input, &mut output);
+}
@@ -0,0 +1,462 @@
+//! A fixed capacity buffer that overwrites its oldest elements
+
+use core::iter::{Chain, FusedIterator};
+use core::mem::MaybeUninit;
+use core::{fmt, ops, ptr, slice};
+
+/// A "history buffer" backed by an array of length `N`
+///
+/// Writing into a history buffer never fails: once the buffer is full, every write overwrites
+/// (and drops) the oldest element. This makes it a good fit for keeping the last `N` samples of
+/// a sensor, or the tail of a log.
+///
+/// `N` must be greater than zero.
+pub struct HistoryBuffer<T, const N: usize> {
+    data: MaybeUninit<[T; N]>,
+    // slot that the next `write` will store its element into
+    write_at: usize,
+    // whether all the slots have been written to at least once
+    filled: bool,
+}
+
+impl<T, const N: usize> HistoryBuffer<T, N> {
+    // NOTE evaluated, at compile time, by `new`
+    const NON_ZERO: () = assert!(N > 0, "HistoryBuffer: the capacity must be greater than zero");
+
+    /// Constructs a new, empty history buffer with a capacity of `N`
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::NON_ZERO;
+
+        HistoryBuffer {
+            data: MaybeUninit::uninit(),
+            write_at: 0,
+            filled: false,
+        }
+    }
+
+    /// Constructs a new history buffer where every slot is filled with `t`
+    ///
+    /// This is handy for statistics such as moving averages, which then don't need to special
+    /// case the warm up period.
+    pub fn new_with(t: T) -> Self
+    where
+        T: Copy,
+    {
+        let mut hb = Self::new();
+        hb.clear_with(t);
+        hb
+    }
+
+    /// Returns the maximum number of elements the buffer can hold
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of elements in the buffer
+    pub fn len(&self) -> usize {
+        if self.filled {
+            N
+        } else {
+            self.write_at
+        }
+    }
+
+    /// Returns `true` if the buffer contains no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if every slot of the buffer has been written to, i.e. if the next `write`
+    /// will overwrite the oldest element
+    pub fn is_full(&self) -> bool {
+        self.filled
+    }
+
+    /// Writes an element into the buffer, overwriting the oldest element if the buffer is full
+    pub fn write(&mut self, t: T) {
+        let slot = unsafe { self.buffer_mut_ptr().add(self.write_at) };
+
+        // NOTE(replace) the element that is being overwritten is moved out, and only dropped once
+        // the buffer is consistent again, so a panicking destructor can't cause a double drop
+        let old = if self.filled {
+            Some(unsafe { ptr::replace(slot, t) })
+        } else {
+            unsafe { ptr::write(slot, t) }
+            None
+        };
+
+        self.write_at += 1;
+        if self.write_at == N {
+            self.write_at = 0;
+            self.filled = true;
+        }
+
+        drop(old);
+    }
+
+    /// Clones and writes all the elements in `other` into the buffer, in order
+    ///
+    /// If `other` is longer than the buffer only its last `N` elements remain in the buffer.
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        for item in other {
+            self.write(item.clone());
+        }
+    }
+
+    /// Returns a reference to the most recently written element, or `None` if the buffer is
+    /// empty
+    pub fn recent(&self) -> Option<&T> {
+        if self.write_at != 0 {
+            Some(unsafe { &*self.buffer_ptr().add(self.write_at - 1) })
+        } else if self.filled {
+            Some(unsafe { &*self.buffer_ptr().add(N - 1) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a reference to the oldest element, or `None` if the buffer is empty
+    pub fn oldest(&self) -> Option<&T> {
+        if self.filled {
+            Some(unsafe { &*self.buffer_ptr().add(self.write_at) })
+        } else if self.write_at != 0 {
+            Some(unsafe { &*self.buffer_ptr() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the elements of the buffer as a slice, in storage order
+    ///
+    /// The elements are *not* ordered by age. Use `as_slices` or `oldest_ordered` for that.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.buffer_ptr(), self.len()) }
+    }
+
+    /// Returns a pair of slices which contain, in order, the elements of the buffer from the
+    /// oldest to the most recent one
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let buffer = self.as_slice();
+
+        if self.filled {
+            (&buffer[self.write_at..], &buffer[..self.write_at])
+        } else {
+            (buffer, &[])
+        }
+    }
+
+    /// Iterates over the elements of the buffer from the oldest to the most recent one
+    pub fn oldest_ordered(&self) -> OldestOrdered<'_, T> {
+        let (a, b) = self.as_slices();
+
+        OldestOrdered {
+            inner: a.iter().chain(b),
+        }
+    }
+
+    /// Drops all the elements of the buffer
+    pub fn clear(&mut self) {
+        let len = self.len();
+
+        // NOTE(filled) reset the state first so a panicking destructor can't cause a double drop
+        self.write_at = 0;
+        self.filled = false;
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.buffer_mut_ptr(), len)) }
+    }
+
+    /// Drops all the elements of the buffer and then fills every slot with `t`
+    pub fn clear_with(&mut self, t: T)
+    where
+        T: Copy,
+    {
+        self.clear();
+
+        for i in 0..N {
+            unsafe { ptr::write(self.buffer_mut_ptr().add(i), t) }
+        }
+        self.filled = true;
+    }
+
+    fn buffer_ptr(&self) -> *const T {
+        self.data.as_ptr() as *const T
+    }
+
+    fn buffer_mut_ptr(&mut self) -> *mut T {
+        self.data.as_mut_ptr() as *mut T
+    }
+}
+
+impl<T, const N: usize> Clone for HistoryBuffer<T, N>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut hb = Self::new();
+        for item in self.as_slice() {
+            // NOTE `self` and `hb` have the same capacity so the elements keep their slots
+            unsafe { ptr::write(hb.buffer_mut_ptr().add(hb.write_at), item.clone()) }
+            hb.write_at += 1;
+        }
+        hb.write_at = self.write_at;
+        hb.filled = self.filled;
+        hb
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for HistoryBuffer<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.oldest_ordered()).finish()
+    }
+}
+
+impl<T, const N: usize> Default for HistoryBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for HistoryBuffer<T, N> {
+    fn drop(&mut self) {
+        let len = self.len();
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.buffer_mut_ptr(), len)) }
+    }
+}
+
+/// Dereferences to the elements of the buffer in storage order; see `as_slice`
+impl<T, const N: usize> ops::Deref for HistoryBuffer<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for HistoryBuffer<T, N> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.write(item);
+        }
+    }
+}
+
+impl<'a, T, const N: usize> Extend<&'a T> for HistoryBuffer<T, N>
+where
+    T: 'a + Clone,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = &'a T>,
+    {
+        self.extend(iter.into_iter().cloned())
+    }
+}
+
+/// An iterator over the elements of a history buffer, from the oldest to the most recent one
+///
+/// This struct is created by the `oldest_ordered` method on `HistoryBuffer`
+pub struct OldestOrdered<'a, T> {
+    inner: Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>,
+}
+
+impl<'a, T> Iterator for OldestOrdered<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for OldestOrdered<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for OldestOrdered<'a, T> {}
+
+impl<'a, T> FusedIterator for OldestOrdered<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use std;
+
+    use HistoryBuffer;
+
+    #[test]
+    fn clone() {
+        let mut hb: HistoryBuffer<i32, 4> = HistoryBuffer::new();
+
+        hb.extend_from_slice(&[0, 1, 2, 3, 4, 5]);
+
+        let mut clone = hb.clone();
+
+        assert!(clone.oldest_ordered().eq(&[2, 3, 4, 5]));
+
+        clone.write(6);
+
+        assert!(clone.oldest_ordered().eq(&[3, 4, 5, 6]));
+        assert!(hb.oldest_ordered().eq(&[2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn debug() {
+        let mut hb: HistoryBuffer<i32, 3> = HistoryBuffer::new();
+
+        hb.extend(0..5);
+
+        assert_eq!(std::format!("{:?}", hb), "[2, 3, 4]");
+    }
+
+    #[test]
+    fn drop() {
+        #[derive(Debug)]
+        struct Droppable;
+        impl Droppable {
+            fn new() -> Self {
+                unsafe {
+                    COUNT += 1;
+                }
+                Droppable
+            }
+        }
+        impl Drop for Droppable {
+            fn drop(&mut self) {
+                unsafe {
+                    COUNT -= 1;
+                }
+            }
+        }
+
+        static mut COUNT: i32 = 0;
+
+        {
+            let mut hb: HistoryBuffer<Droppable, 2> = HistoryBuffer::new();
+            hb.write(Droppable::new());
+            assert_eq!(unsafe { COUNT }, 1);
+        }
+
+        assert_eq!(unsafe { COUNT }, 0);
+
+        {
+            let mut hb: HistoryBuffer<Droppable, 2> = HistoryBuffer::new();
+            hb.write(Droppable::new());
+            hb.write(Droppable::new());
+            hb.write(Droppable::new());
+            assert_eq!(unsafe { COUNT }, 2);
+            hb.clear();
+            assert_eq!(unsafe { COUNT }, 0);
+            hb.write(Droppable::new());
+        }
+
+        assert_eq!(unsafe { COUNT }, 0);
+    }
+
+    #[test]
+    fn drop_panic() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        use std::panic::{self, AssertUnwindSafe};
+
+        static DROPS: [AtomicUsize; 3] = [const { AtomicUsize::new(0) }; 3];
+
+        struct Bomb(usize);
+        impl Drop for Bomb {
+            fn drop(&mut self) {
+                DROPS[self.0].fetch_add(1, Ordering::SeqCst);
+                if self.0 == 0 {
+                    panic!("boom");
+                }
+            }
+        }
+
+        {
+            let mut hb: HistoryBuffer<Bomb, 1> = HistoryBuffer::new();
+            hb.write(Bomb(0));
+
+            // overwriting `Bomb(0)` panics
+            assert!(panic::catch_unwind(AssertUnwindSafe(|| hb.write(Bomb(1)))).is_err());
+
+            hb.write(Bomb(2));
+        }
+
+        // every element was dropped exactly once
+        assert!(DROPS.iter().all(|drops| drops.load(Ordering::SeqCst) == 1));
+    }
+
+    #[test]
+    fn new_with() {
+        let mut hb: HistoryBuffer<u32, 4> = HistoryBuffer::new_with(0);
+
+        assert!(hb.is_full());
+        assert_eq!(hb.len(), 4);
+
+        hb.write(8);
+        hb.write(4);
+
+        // moving average
+        assert_eq!(hb.iter().sum::<u32>() / hb.len() as u32, 3);
+    }
+
+    #[test]
+    fn oldest_ordered() {
+        let mut hb: HistoryBuffer<i32, 4> = HistoryBuffer::new();
+
+        assert_eq!(hb.oldest_ordered().next(), None);
+
+        hb.extend_from_slice(&[0, 1, 2]);
+
+        assert!(hb.oldest_ordered().eq(&[0, 1, 2]));
+        assert_eq!(hb.as_slices(), (&[0, 1, 2][..], &[][..]));
+
+        hb.extend_from_slice(&[3, 4, 5]);
+
+        assert!(hb.oldest_ordered().eq(&[2, 3, 4, 5]));
+        assert!(hb.oldest_ordered().rev().eq(&[5, 4, 3, 2]));
+        assert_eq!(hb.oldest_ordered().len(), 4);
+        assert_eq!(hb.as_slices(), (&[2, 3][..], &[4, 5][..]));
+        assert_eq!(hb.as_slice(), [4, 5, 2, 3]);
+    }
+
+    #[test]
+    fn write() {
+        let mut hb: HistoryBuffer<i32, 4> = HistoryBuffer::new();
+
+        assert!(hb.is_empty());
+        assert_eq!(hb.recent(), None);
+        assert_eq!(hb.oldest(), None);
+
+        hb.write(0);
+        hb.write(1);
+
+        assert_eq!(hb.len(), 2);
+        assert!(!hb.is_full());
+        assert_eq!(hb.recent(), Some(&1));
+        assert_eq!(hb.oldest(), Some(&0));
+
+        hb.write(2);
+        hb.write(3);
+
+        assert!(hb.is_full());
+        assert_eq!(hb.recent(), Some(&3));
+        assert_eq!(hb.oldest(), Some(&0));
+
+        hb.write(4);
+
+        assert_eq!(hb.len(), 4);
+        assert_eq!(hb.recent(), Some(&4));
+        assert_eq!(hb.oldest(), Some(&1));
+    }
+}
@@ -1,21 +1,52 @@
 //! `static` friendly data structures that don't require dynamic memory
 //! allocation
 
-#![feature(const_fn)]
-#![feature(shared)]
-#![feature(unsize)]
 #![no_std]
 
-extern crate untagged_option;
+#[cfg(test)]
+extern crate std;
 
+#[cfg(target_has_atomic = "8")]
+#[macro_use]
+mod singleton;
+
+pub use histbuf::HistoryBuffer;
 pub use vec::Vec;
 pub use ring_buffer::RingBuffer;
+pub use string::String;
 
+pub mod histbuf;
+#[cfg(target_has_atomic = "ptr")]
+pub mod mpmc;
+#[cfg(target_has_atomic = "ptr")]
+pub mod mpsc;
+#[cfg(target_has_atomic = "64")]
+pub mod pool;
 pub mod ring_buffer;
-mod vec;
+mod string;
+pub mod vec;
+
+use core::fmt;
+
+#[doc(hidden)]
+pub mod __export {
+    pub use core::mem::MaybeUninit;
+    pub use core::ptr::addr_of_mut;
+    #[cfg(target_has_atomic = "8")]
+    pub use core::sync::atomic::{AtomicBool, Ordering};
+}
 
 /// Error
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Error {
+    /// The collection doesn't have enough free space to complete the operation
     Full,
 }
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Full => f.write_str("collection is full"),
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! A bounded multi-producer multi-consumer queue
+//!
+//! The queue is Dmitry Vyukov's [bounded MPMC queue]: every slot carries a sequence number that
+//! tells producers and consumers whether it's their turn to use the slot, so claiming a slot
+//! takes a single compare-and-swap and there's no global lock.
+//!
+//! The queue requires compare-and-swap operations so it's not available on targets that lack
+//! them, like ARMv6-M (Cortex-M0).
+//!
+//! [bounded MPMC queue]: http://www.1024cores.net/home/lock-free-algorithms/queues/bounded-mpmc-queue
+
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// A bounded multi-producer multi-consumer queue that can hold `N` items
+///
+/// `N` must be a power of two greater than one; this is checked at compile time. All the
+/// operations take `&self` so the queue can be placed in a `static` and used from several threads
+/// or interrupt handlers.
+///
+/// ``` compile_fail
+/// use heapless::mpmc::Queue;
+///
+/// // a single slot is rejected
+/// let q: Queue<u8, 1> = Queue::new();
+/// ```
+pub struct Queue<T, const N: usize> {
+    buffer: [Slot<T>; N],
+    // position of the next slot to dequeue from
+    dequeue_pos: AtomicUsize,
+    // position of the next slot to enqueue into
+    enqueue_pos: AtomicUsize,
+}
+
+struct Slot<T> {
+    // NOTE the slot at index `i` is free for the enqueue at position `pos` when
+    // `sequence == pos`, and holds the item for the dequeue at position `pos` when
+    // `sequence == pos + 1`. Positions are mapped to slots with `pos % N`
+    sequence: AtomicUsize,
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+// NOTE(unsafe) the sequence numbers give each slot a single owner at a time, so the queue only
+// ever moves items between threads
+unsafe impl<T, const N: usize> Sync for Queue<T, N> where T: Send {}
+
+impl<T, const N: usize> Queue<T, N> {
+    // NOTE evaluated, at compile time, by `new`. Positions run freely (wrapping around at
+    // `usize::MAX`) which only maps them consistently to slots if `N` divides the range of `usize`.
+    // With a single slot the sequence numbers of "free" and "full" laps overlap, so the algorithm
+    // needs at least two
+    const POW2: () = assert!(
+        N > 1 && N.is_power_of_two(),
+        "mpmc::Queue: `N` must be a power of two greater than one"
+    );
+
+    /// Creates an empty queue
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::POW2;
+
+        let mut buffer = [const { Slot::new(0) }; N];
+        let mut i = 0;
+        while i < N {
+            buffer[i] = Slot::new(i);
+            i += 1;
+        }
+
+        Queue {
+            buffer,
+            dequeue_pos: AtomicUsize::new(0),
+            enqueue_pos: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the maximum number of items the queue can hold
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the item in the front of the queue, or `None` if the queue is empty
+    pub fn dequeue(&self) -> Option<T> {
+        let mut pos = self.dequeue_pos.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &self.buffer[pos & (N - 1)];
+            // NOTE(Acquire) pairs with the `Release` store in `enqueue`; this makes the item
+            // visible to us
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let diff = sequence.wrapping_sub(pos.wrapping_add(1)) as isize;
+
+            if diff == 0 {
+                // the slot holds an item; try to claim it
+                match self.dequeue_pos.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let item = unsafe { (*slot.data.get()).as_ptr().read() };
+                        // NOTE(Release) the item must be moved out before the slot is handed to
+                        // the enqueue that will use it in the next lap
+                        slot.sequence
+                            .store(pos.wrapping_add(N), Ordering::Release);
+                        return Some(item);
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                // the slot hasn't been filled in this lap: the queue is empty
+                return None;
+            } else {
+                // another consumer claimed the slot; catch up
+                pos = self.dequeue_pos.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Adds an `item` to the end of the queue
+    ///
+    /// Returns back the `item` if the queue is full
+    pub fn enqueue(&self, item: T) -> Result<(), T> {
+        let mut pos = self.enqueue_pos.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &self.buffer[pos & (N - 1)];
+            // NOTE(Acquire) pairs with the `Release` store in `dequeue`; this makes sure the
+            // previous item has been moved out of the slot
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let diff = sequence.wrapping_sub(pos) as isize;
+
+            if diff == 0 {
+                // the slot is free; try to claim it
+                match self.enqueue_pos.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // NOTE(write) the slot is uninitialized
+                        unsafe { (*slot.data.get()).as_mut_ptr().write(item) }
+                        // NOTE(Release) the item must be written before consumers can see it
+                        slot.sequence
+                            .store(pos.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                // the slot still holds the item of the previous lap: the queue is full
+                return Err(item);
+            } else {
+                // another producer claimed the slot; catch up
+                pos = self.enqueue_pos.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> Default for Queue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for Queue<T, N> {
+    fn drop(&mut self) {
+        while self.dequeue().is_some() {}
+    }
+}
+
+impl<T> Slot<T> {
+    const fn new(sequence: usize) -> Self {
+        Slot {
+            sequence: AtomicUsize::new(sequence),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::vec::Vec;
+
+    use mpmc::Queue;
+
+    #[test]
+    fn drop() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        struct Droppable;
+        impl Droppable {
+            fn new() -> Self {
+                COUNT.fetch_add(1, Ordering::SeqCst);
+                Droppable
+            }
+        }
+        impl Drop for Droppable {
+            fn drop(&mut self) {
+                COUNT.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        {
+            let q: Queue<Droppable, 4> = Queue::new();
+            assert!(q.enqueue(Droppable::new()).is_ok());
+            assert!(q.enqueue(Droppable::new()).is_ok());
+            q.dequeue().unwrap();
+            assert_eq!(COUNT.load(Ordering::SeqCst), 1);
+        }
+
+        assert_eq!(COUNT.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn sanity() {
+        let q: Queue<i32, 2> = Queue::new();
+
+        assert_eq!(q.capacity(), 2);
+        assert_eq!(q.dequeue(), None);
+
+        // several laps around the buffer
+        for i in 0..10 {
+            q.enqueue(i).unwrap();
+            q.enqueue(i + 1).unwrap();
+            assert_eq!(q.enqueue(i + 2), Err(i + 2));
+
+            assert_eq!(q.dequeue(), Some(i));
+            assert_eq!(q.dequeue(), Some(i + 1));
+            assert_eq!(q.dequeue(), None);
+        }
+    }
+
+    #[test]
+    fn static_() {
+        static Q: Queue<u32, 8> = Queue::new();
+
+        let producer = thread::spawn(|| {
+            for i in 0..1_000 {
+                while Q.enqueue(i).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+
+        for i in 0..1_000 {
+            loop {
+                if let Some(j) = Q.dequeue() {
+                    assert_eq!(i, j);
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn stress() {
+        const PRODUCERS: u32 = 4;
+        const CONSUMERS: usize = 4;
+        const ITEMS: u32 = 10_000;
+
+        let q: Queue<(u32, u32), 16> = Queue::new();
+
+        let received = thread::scope(|s| {
+            for p in 0..PRODUCERS {
+                let q = &q;
+                s.spawn(move || {
+                    for i in 0..ITEMS {
+                        let mut item = (p, i);
+                        while let Err(rejected) = q.enqueue(item) {
+                            item = rejected;
+                            thread::yield_now();
+                        }
+                    }
+                });
+            }
+
+            let consumers = (0..CONSUMERS)
+                .map(|_| {
+                    let q = &q;
+                    s.spawn(move || {
+                        let mut received = Vec::new();
+                        // NOTE each consumer dequeues an equal share of the items
+                        let mut last = [None; PRODUCERS as usize];
+                        while received.len() < (PRODUCERS * ITEMS) as usize / CONSUMERS {
+                            match q.dequeue() {
+                                Some((p, i)) => {
+                                    // items from one producer are dequeued in order
+                                    assert!(last[p as usize] < Some(i));
+                                    last[p as usize] = Some(i);
+                                    received.push((p, i));
+                                }
+                                None => thread::yield_now(),
+                            }
+                        }
+                        received
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            consumers
+                .into_iter()
+                .flat_map(|c| c.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        // every item was dequeued exactly once
+        let mut received = received;
+        received.sort();
+        let expected = (0..PRODUCERS)
+            .flat_map(|p| (0..ITEMS).map(move |i| (p, i)))
+            .collect::<Vec<_>>();
+        assert_eq!(received, expected);
+        assert_eq!(q.dequeue(), None);
+    }
+}
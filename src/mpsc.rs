@@ -0,0 +1,208 @@
+//! A bounded multi-producer single-consumer queue
+//!
+//! `Queue::split` hands out a `Producer`, which can be cloned as many times as needed (e.g. one
+//! per interrupt handler), and a unique `Consumer` (e.g. for the main loop). Enqueuing is
+//! lock-free: it never waits for another producer, so it can be done from an interrupt handler
+//! that preempted another producer.
+//!
+//! This is built on top of `mpmc::Queue`, and has the same requirements.
+//!
+//! Note that the consumer sees the items in the order in which the producers *claimed* their
+//! slots, and an item only becomes visible once it has been written into its slot. A producer
+//! that is preempted between the two (e.g. by an interrupt handler that enqueues too) holds back
+//! every item enqueued after it, including the ones from higher priority contexts: `dequeue`
+//! returns `None` until the preempted producer resumes and finishes its `enqueue`.
+
+use mpmc;
+
+/// A bounded multi-producer single-consumer queue that can hold `N` items
+///
+/// `N` must be a power of two greater than one; this is checked at compile time.
+pub struct Queue<T, const N: usize> {
+    inner: mpmc::Queue<T, N>,
+}
+
+impl<T, const N: usize> Queue<T, N> {
+    /// Creates an empty queue
+    pub const fn new() -> Self {
+        Queue {
+            inner: mpmc::Queue::new(),
+        }
+    }
+
+    /// Returns the maximum number of items the queue can hold
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Splits the queue into producer and consumer end points
+    ///
+    /// The end points borrow the queue for the lifetime `'q`. The producer can be cloned to get
+    /// more producers.
+    pub fn split<'q>(&'q mut self) -> (Producer<'q, T, N>, Consumer<'q, T, N>) {
+        (
+            Producer { queue: &self.inner },
+            Consumer { queue: &self.inner },
+        )
+    }
+}
+
+impl<T, const N: usize> Default for Queue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The consumer end point of a `Queue`; it can dequeue items from the queue
+// NOTE the consumer is unique: it's not `Clone` and `dequeue` takes `&mut self`
+pub struct Consumer<'q, T, const N: usize> {
+    queue: &'q mpmc::Queue<T, N>,
+}
+
+impl<'q, T, const N: usize> Consumer<'q, T, N> {
+    /// Returns the item in the front of the queue, or `None` if the queue is empty
+    pub fn dequeue(&mut self) -> Option<T> {
+        self.queue.dequeue()
+    }
+
+    /// Returns the maximum number of items the queue can hold
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+/// A producer end point of a `Queue`; it can enqueue items into the queue
+///
+/// Producers can be cloned, and shared or sent between contexts (e.g. interrupt handlers)
+pub struct Producer<'q, T, const N: usize> {
+    queue: &'q mpmc::Queue<T, N>,
+}
+
+impl<'q, T, const N: usize> Clone for Producer<'q, T, N> {
+    fn clone(&self) -> Self {
+        Producer { queue: self.queue }
+    }
+}
+
+impl<'q, T, const N: usize> Producer<'q, T, N> {
+    /// Adds an `item` to the end of the queue
+    ///
+    /// Returns back the `item` if the queue is full
+    pub fn enqueue(&self, item: T) -> Result<(), T> {
+        self.queue.enqueue(item)
+    }
+
+    /// Returns the maximum number of items the queue can hold
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::vec::Vec;
+
+    use mpsc::Queue;
+
+    #[test]
+    fn sanity() {
+        let mut q: Queue<i32, 4> = Queue::new();
+        let (p, mut c) = q.split();
+        let p2 = p.clone();
+
+        assert_eq!(c.dequeue(), None);
+
+        p.enqueue(0).unwrap();
+        p2.enqueue(1).unwrap();
+        p.enqueue(2).unwrap();
+        p2.enqueue(3).unwrap();
+
+        assert_eq!(p.enqueue(4), Err(4));
+
+        assert_eq!(c.dequeue(), Some(0));
+        assert_eq!(c.dequeue(), Some(1));
+        assert_eq!(c.dequeue(), Some(2));
+        assert_eq!(c.dequeue(), Some(3));
+        assert_eq!(c.dequeue(), None);
+    }
+
+    #[test]
+    fn scoped() {
+        const PRODUCERS: u32 = 4;
+        const ITEMS: u32 = 10_000;
+
+        let mut q: Queue<(u32, u32), 8> = Queue::new();
+        let (p, mut c) = q.split();
+
+        thread::scope(|s| {
+            for id in 0..PRODUCERS {
+                let p = p.clone();
+                s.spawn(move || {
+                    for i in 0..ITEMS {
+                        let mut item = (id, i);
+                        while let Err(rejected) = p.enqueue(item) {
+                            item = rejected;
+                            thread::yield_now();
+                        }
+                    }
+                });
+            }
+
+            let mut next = [0; PRODUCERS as usize];
+            let mut received = 0;
+            while received < PRODUCERS * ITEMS {
+                match c.dequeue() {
+                    Some((id, i)) => {
+                        // items from one producer are dequeued in order
+                        assert_eq!(next[id as usize], i);
+                        next[id as usize] += 1;
+                        received += 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+
+            assert!(next.iter().all(|&n| n == ITEMS));
+        });
+    }
+
+    #[test]
+    fn threads() {
+        let q = singleton!(: Queue<u32, 8> = Queue::new()).unwrap();
+
+        let (p, mut c) = q.split();
+
+        let producers = (0..2)
+            .map(|_| {
+                let p = p.clone();
+                thread::spawn(move || {
+                    for i in 0..1_000 {
+                        while p.enqueue(i).is_err() {
+                            thread::yield_now();
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut sum = 0;
+        let mut received = 0;
+        while received < 2_000 {
+            match c.dequeue() {
+                Some(i) => {
+                    sum += i;
+                    received += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        assert_eq!(sum, 2 * (0..1_000).sum::<u32>());
+        assert_eq!(c.dequeue(), None);
+    }
+}
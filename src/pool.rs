@@ -0,0 +1,472 @@
+//! A lock-free memory pool of fixed size blocks
+//!
+//! A `Pool<T>` manages blocks of memory, `Node<T>`s, that can hold one `T` each. The pool is
+//! `grow`n by handing it a `'static` region of nodes; `alloc` then moves a value into a free block
+//! and returns a `Box<T>` that gives the block back to the pool when dropped.
+//!
+//! The free blocks are kept in a Treiber stack. To make the stack ABA-safe on multi-core targets
+//! its head is a *tagged* reference: a 32-bit counter packed next to the reference to the first
+//! free node is bumped on every update, so a stale compare-and-swap fails even if the head refers
+//! to the same node again. The head is a 64-bit word: nodes are referred to by their (signed)
+//! 32-bit offset from the first node the pool was grown with, counted in multiples of the node
+//! alignment. On 32-bit targets every node can be encoded; on 64-bit targets nodes must lie within
+//! `2^31` node alignments (at least 16 GiB) of the first node of the pool.
+//!
+//! A narrower tag wraps around too quickly to be safe on multi-core targets, so the module is only
+//! available on targets with 64-bit compare-and-swap (`target_has_atomic = "64"`).
+//!
+//! # Example
+//!
+//! ```
+//! #[macro_use(singleton)]
+//! extern crate heapless;
+//!
+//! use heapless::pool::{Node, Pool};
+//!
+//! static POOL: Pool<[u8; 128]> = Pool::new();
+//!
+//! fn main() {
+//!     let memory = singleton!(: [Node<[u8; 128]>; 4] = [const { Node::new() }; 4]).unwrap();
+//!     POOL.grow(memory).ok().unwrap();
+//!
+//!     let mut packet = POOL.alloc([0; 128]).ok().unwrap();
+//!     packet[0] = 1;
+//!
+//!     // returns the block to the pool
+//!     drop(packet);
+//! }
+//! ```
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+use core::{fmt, ops};
+
+// NOTE the head is `u64` wide; see the module documentation
+const OFFSET_BITS: u32 = 32;
+
+// NOTE the offset lives in the lower `OFFSET_BITS` bits of the head, the tag in the rest
+const OFFSET_MASK: u64 = (1 << OFFSET_BITS) - 1;
+
+// NOTE(NULL) the most negative offset is never handed out by `grow`; it marks an empty pool
+const NULL: u64 = 1 << (OFFSET_BITS - 1);
+
+/// A lock-free pool of `T`-sized memory blocks
+pub struct Pool<T> {
+    // tagged offset of the first free node
+    head: AtomicU64,
+    // first node the pool was grown with; offsets are relative to it
+    base: AtomicPtr<Node<T>>,
+    _marker: PhantomData<*mut Node<T>>,
+}
+
+// NOTE(unsafe) the pool only hands out each block once, and values of type `T` are moved between
+// contexts through the blocks
+unsafe impl<T> Sync for Pool<T> where T: Send {}
+unsafe impl<T> Send for Pool<T> where T: Send {}
+
+/// A memory block that can hold one `T`
+///
+/// Nodes are handed to a `Pool` with `Pool::grow`.
+pub struct Node<T> {
+    // next free node; only meaningful while the node is in the pool
+    next: AtomicPtr<Node<T>>,
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Node<T> {
+    /// Creates a new, free, memory block
+    pub const fn new() -> Self {
+        Node {
+            next: AtomicPtr::new(ptr::null_mut()),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+impl<T> Default for Node<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Pool<T> {
+    /// Creates an empty pool
+    pub const fn new() -> Self {
+        Pool {
+            head: AtomicU64::new(NULL),
+            base: AtomicPtr::new(ptr::null_mut()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Adds the `nodes` to the pool, and returns the number of blocks added
+    ///
+    /// Returns back the `nodes`, without adding any of them, if one of them is too far from the
+    /// first node the pool was grown with to be encoded in the head of the pool (see the module
+    /// documentation)
+    pub fn grow(&self, nodes: &'static mut [Node<T>]) -> Result<usize, &'static mut [Node<T>]> {
+        let first = match nodes.first_mut() {
+            Some(first) => first as *mut Node<T>,
+            None => return Ok(0),
+        };
+
+        loop {
+            let base = self.base.load(Ordering::Acquire);
+            let candidate = if base.is_null() { first } else { base };
+
+            // NOTE check all the nodes before handing any of them to the pool
+            let fits = nodes.iter_mut().all(|node| {
+                let offset = offset(candidate, node) as i64;
+                -(1 << (OFFSET_BITS - 1)) < offset && offset < 1 << (OFFSET_BITS - 1)
+            });
+            if !fits {
+                return Err(nodes);
+            }
+
+            // the first grow sets `base`; if another grow beat us to it check against its base
+            if base.is_null()
+                && self
+                    .base
+                    .compare_exchange(base, first, Ordering::AcqRel, Ordering::Acquire)
+                    .is_err()
+            {
+                continue;
+            }
+
+            break;
+        }
+
+        for node in nodes.iter_mut() {
+            self.push(NonNull::from(node));
+        }
+        Ok(nodes.len())
+    }
+
+    /// Moves `value` into a free block of the pool
+    ///
+    /// Returns back the `value` if the pool has no free blocks
+    pub fn alloc(&'static self, value: T) -> Result<Box<T>, T> {
+        match self.pop() {
+            Some(node) => {
+                // NOTE(write) the block is uninitialized
+                unsafe { (*node.as_ref().data.get()).as_mut_ptr().write(value) }
+                Ok(Box { node, pool: self })
+            }
+            None => Err(value),
+        }
+    }
+
+    fn push(&self, node: NonNull<Node<T>>) {
+        // NOTE(Acquire) `base` is set before the first node is pushed
+        let base = self.base.load(Ordering::Acquire);
+
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let (next, tag) = unpack(base, head);
+            unsafe { node.as_ref().next.store(next, Ordering::Relaxed) }
+
+            // NOTE(Release) pairs with the `Acquire` in `pop`; makes `next` (and, when a `Box` is
+            // dropped, the destruction of its value) visible to the next owner of the node
+            match self.head.compare_exchange_weak(
+                head,
+                pack(base, node.as_ptr(), tag.wrapping_add(1)),
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    fn pop(&self) -> Option<NonNull<Node<T>>> {
+        let mut head = self.head.load(Ordering::Acquire);
+        let base = self.base.load(Ordering::Acquire);
+        loop {
+            let (ptr, tag) = unpack(base, head);
+            let node = NonNull::new(ptr)?;
+            // NOTE(unsafe) the node may have been popped by someone else in the meantime, but
+            // nodes are `'static` so it's still valid memory; in that case the tag has changed
+            // and the compare-and-swap below fails
+            let next = unsafe { node.as_ref().next.load(Ordering::Relaxed) };
+
+            match self.head.compare_exchange_weak(
+                head,
+                pack(base, next, tag.wrapping_add(1)),
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(node),
+                Err(current) => head = current,
+            }
+        }
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// offset of `node` from `base`, in multiples of the node alignment
+fn offset<T>(base: *mut Node<T>, node: *mut Node<T>) -> isize {
+    // NOTE both pointers are aligned so the division is exact
+    (node as isize).wrapping_sub(base as isize) / mem::align_of::<Node<T>>() as isize
+}
+
+fn pack<T>(base: *mut Node<T>, node: *mut Node<T>, tag: u64) -> u64 {
+    let offset = if node.is_null() {
+        NULL
+    } else {
+        offset(base, node) as u64 & OFFSET_MASK
+    };
+
+    tag << OFFSET_BITS | offset
+}
+
+fn unpack<T>(base: *mut Node<T>, head: u64) -> (*mut Node<T>, u64) {
+    let tag = head >> OFFSET_BITS;
+    let offset = head & OFFSET_MASK;
+
+    if offset == NULL {
+        return (ptr::null_mut(), tag);
+    }
+
+    // sign-extend the offset
+    let offset = offset as u32 as i32;
+    let node = (base as isize)
+        .wrapping_add((offset as isize).wrapping_mul(mem::align_of::<Node<T>>() as isize));
+
+    (node as *mut Node<T>, tag)
+}
+
+/// A pointer to a `T` stored in a block of a `Pool`
+///
+/// The value is dropped, and the block returned to the pool, when the `Box` is dropped.
+pub struct Box<T>
+where
+    T: 'static,
+{
+    node: NonNull<Node<T>>,
+    pool: &'static Pool<T>,
+}
+
+// NOTE(unsafe) a `Box` owns its value, like `&mut T` does
+unsafe impl<T> Send for Box<T> where T: Send {}
+unsafe impl<T> Sync for Box<T> where T: Sync {}
+
+impl<T> ops::Deref for Box<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*(*self.node.as_ref().data.get()).as_ptr() }
+    }
+}
+
+impl<T> ops::DerefMut for Box<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *(*self.node.as_ref().data.get()).as_mut_ptr() }
+    }
+}
+
+impl<T> Drop for Box<T> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(&mut **self as *mut T) }
+        self.pool.push(self.node);
+    }
+}
+
+impl<T> fmt::Debug for Box<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <T as fmt::Debug>::fmt(self, f)
+    }
+}
+
+impl<T> fmt::Display for Box<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <T as fmt::Display>::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ptr;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use pool::{Node, Pool};
+
+    #[test]
+    fn drop() {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        struct Droppable;
+        impl Droppable {
+            fn new() -> Self {
+                COUNT.fetch_add(1, Ordering::SeqCst);
+                Droppable
+            }
+        }
+        impl Drop for Droppable {
+            fn drop(&mut self) {
+                COUNT.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        static POOL: Pool<Droppable> = Pool::new();
+        static mut MEMORY: [Node<Droppable>; 1] = [const { Node::new() }; 1];
+
+        POOL.grow(unsafe { &mut *ptr::addr_of_mut!(MEMORY) }).ok().unwrap();
+
+        let a = POOL.alloc(Droppable::new()).ok().unwrap();
+
+        assert_eq!(COUNT.load(Ordering::SeqCst), 1);
+
+        // the rejected value is handed back, not dropped
+        let b = POOL.alloc(Droppable::new()).err().unwrap();
+
+        assert_eq!(COUNT.load(Ordering::SeqCst), 2);
+
+        {
+            let _a = a;
+            let _b = b;
+        }
+
+        assert_eq!(COUNT.load(Ordering::SeqCst), 0);
+
+        // the block is back in the pool
+        let a = POOL.alloc(Droppable::new()).ok().unwrap();
+        {
+            let _a = a;
+        }
+
+        assert_eq!(COUNT.load(Ordering::SeqCst), 0);
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn grow_too_far() {
+        static POOL: Pool<u32> = Pool::new();
+        static mut MEMORY: [Node<u32>; 2] = [const { Node::new() }; 2];
+
+        let memory = unsafe { &mut *ptr::addr_of_mut!(MEMORY) };
+
+        // pretend the pool was first grown with a node 1 TiB away; it's only used for arithmetic
+        let far = (memory.as_ptr() as usize).wrapping_add(1 << 40);
+        POOL.base.store(far as *mut Node<u32>, Ordering::Relaxed);
+
+        let memory = POOL.grow(memory).err().unwrap();
+
+        // none of the nodes was added
+        assert_eq!(memory.len(), 2);
+        assert!(POOL.alloc(0).is_err());
+    }
+
+    #[test]
+    fn regions() {
+        static POOL: Pool<u32> = Pool::new();
+        static mut A: [Node<u32>; 2] = [const { Node::new() }; 2];
+        static mut B: [Node<u32>; 1] = [const { Node::new() }; 1];
+
+        let (a0, a1) = unsafe { (*ptr::addr_of_mut!(A)).split_at_mut(1) };
+
+        // nodes before and after the first node of the pool
+        assert_eq!(POOL.grow(a1).ok(), Some(1));
+        assert_eq!(POOL.grow(a0).ok(), Some(1));
+        assert_eq!(POOL.grow(unsafe { &mut *ptr::addr_of_mut!(B) }).ok(), Some(1));
+        assert_eq!(POOL.grow(&mut []).ok(), Some(0));
+
+        let blocks = [
+            POOL.alloc(0).ok().unwrap(),
+            POOL.alloc(1).ok().unwrap(),
+            POOL.alloc(2).ok().unwrap(),
+        ];
+
+        assert!(POOL.alloc(3).is_err());
+        assert!(blocks.iter().map(|b| **b).eq(0..3));
+    }
+
+    #[test]
+    fn sanity() {
+        static POOL: Pool<[u8; 16]> = Pool::new();
+        static mut MEMORY: [Node<[u8; 16]>; 2] = [const { Node::new() }; 2];
+
+        assert!(POOL.alloc([0; 16]).is_err());
+
+        assert_eq!(POOL.grow(unsafe { &mut *ptr::addr_of_mut!(MEMORY) }).ok(), Some(2));
+
+        let mut a = POOL.alloc([0; 16]).ok().unwrap();
+        let b = POOL.alloc([1; 16]).ok().unwrap();
+
+        assert_eq!(POOL.alloc([2; 16]).err(), Some([2; 16]));
+
+        a[0] = 42;
+
+        assert_eq!(a[..2], [42, 0]);
+        assert_eq!(*b, [1; 16]);
+
+        {
+            let _b = b;
+        }
+
+        let c = POOL.alloc([3; 16]).ok().unwrap();
+
+        assert_eq!(*c, [3; 16]);
+        assert_eq!(a[0], 42);
+    }
+
+    #[test]
+    fn threads() {
+        const THREADS: usize = 4;
+        const ROUNDS: usize = 10_000;
+
+        static POOL: Pool<usize> = Pool::new();
+        static mut MEMORY: [Node<usize>; 3] = [const { Node::new() }; 3];
+
+        POOL.grow(unsafe { &mut *ptr::addr_of_mut!(MEMORY) }).ok().unwrap();
+
+        let threads = (0..THREADS)
+            .map(|id| {
+                thread::spawn(move || {
+                    for _ in 0..ROUNDS {
+                        let mut block = loop {
+                            match POOL.alloc(id) {
+                                Ok(block) => break block,
+                                Err(_) => thread::yield_now(),
+                            }
+                        };
+
+                        // nobody else owns the block
+                        thread::yield_now();
+                        assert_eq!(*block, id);
+                        *block = !id;
+                        thread::yield_now();
+                        assert_eq!(*block, !id);
+                    }
+                })
+            })
+            .collect::<std::vec::Vec<_>>();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // all the blocks were returned
+        let a = POOL.alloc(0).ok().unwrap();
+        let b = POOL.alloc(1).ok().unwrap();
+        let c = POOL.alloc(2).ok().unwrap();
+
+        assert!(POOL.alloc(3).is_err());
+        assert_eq!((*a, *b, *c), (0, 1, 2));
+    }
+}
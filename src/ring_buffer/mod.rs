@@ -1,87 +1,355 @@
-use core::marker::{PhantomData, Unsize};
-use core::ptr;
+use core::cell::UnsafeCell;
+use core::cmp;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
+use core::mem::MaybeUninit;
+use core::{ops, ptr, slice};
+use core::sync::atomic::Ordering;
 
-use untagged_option::UntaggedOption;
-
-use Error;
-
-pub use self::spsc::{Consumer, Producer};
+pub use self::spsc::{Consumer, GrantR, GrantW, Producer};
+pub use self::uxx::Uxx;
 
 mod spsc;
+mod uxx;
 
-/// An statically allocated ring buffer backed by an array with type `A`
-pub struct RingBuffer<T, A>
+/// An statically allocated ring buffer backed by an array of length `N`
+///
+/// The ring buffer can hold `N` elements. When `N` is a power of two its indices are mapped to
+/// slots of the array by masking; otherwise with a comparison and a subtraction. Neither needs a
+/// division, which is a software routine on cores without a hardware divider (e.g. Cortex-M0).
+/// The choice is made at compile time.
+///
+/// `U` is the type of the `head` and `tail` indices: `u8`, `u16` or `usize`. A narrower type makes
+/// the ring buffer smaller, but limits `N` to half the range of `U`, e.g. `128` for `u8`.
+pub struct RingBuffer<T, const N: usize, U = usize>
 where
-    // FIXME(rust-lang/rust#44580) use "const generics" instead of `Unsize`
-    A: Unsize<[T]>,
+    U: Uxx,
 {
-    _marker: PhantomData<[T]>,
-    buffer: UntaggedOption<A>,
+    // NOTE(UnsafeCell) the `Producer` and the `Consumer` access the buffer through a shared
+    // reference
+    buffer: UnsafeCell<MaybeUninit<[T; N]>>,
     // this is from where we dequeue items
-    head: usize,
+    head: U::Atomic,
     // this is where we enqueue new items
-    tail: usize,
+    tail: U::Atomic,
 }
 
-impl<T, A> RingBuffer<T, A>
+impl<T, const N: usize, U> RingBuffer<T, N, U>
 where
-    A: Unsize<[T]>,
+    U: Uxx,
 {
-    /// Creates an empty ring buffer with capacity equals to the length of the array `A` *minus
-    /// one*.
+    // NOTE(POW2) the `head` and `tail` indices count modulo `2 * N`, not modulo `N`, so that a
+    // full queue (`tail - head == N`) can be told apart from an empty one (`tail == head`)
+    // without keeping a slot free. When `N` is a power of two `2 * N` divides the range of `U` so
+    // the indices can simply run freely, wrapping around at `U::MAX`
+    const POW2: bool = N.is_power_of_two();
+
+    // NOTE evaluated, at compile time, by `new`. The indices must be able to count up to
+    // `2 * N - 1`, or, when they run freely, up to `N` (the length of a full queue)
+    const INDEX_FITS: () = assert!(
+        N <= U::MAX / 2 + 1,
+        "RingBuffer: `N` is too large for the index type `U`"
+    );
+
+    /// Creates an empty ring buffer with a capacity of `N`
+    ///
+    /// Fails to compile if `N` is greater than half the range of the index type `U`
     pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::INDEX_FITS;
+
         RingBuffer {
-            _marker: PhantomData,
-            buffer: UntaggedOption::none(),
-            head: 0,
-            tail: 0,
+            // NOTE(zeroed) a one-time cost (none for a `static`) that lets the `u8` grants of the
+            // `spsc` module hand out free space as initialized bytes
+            buffer: UnsafeCell::new(MaybeUninit::zeroed()),
+            head: U::ZERO,
+            tail: U::ZERO,
         }
     }
 
-    pub fn capacity(&self) -> usize {
-        let buffer: &[T] = unsafe { self.buffer.as_ref() };
-        buffer.len() - 1
+    /// Returns the maximum number of elements the ring buffer can hold
+    pub const fn capacity(&self) -> usize {
+        N
     }
 
     pub fn dequeue(&mut self) -> Option<T> {
-        let n = self.capacity() + 1;
-        let buffer: &[T] = unsafe { self.buffer.as_ref() };
+        let (head, tail) = self.indices();
 
-        if self.head != self.tail {
-            let item = unsafe { ptr::read(&buffer[self.head]) };
-            self.head = (self.head + 1) % n;
+        if head != tail {
+            let item = unsafe { ptr::read(self.buffer_ptr().add(Self::mask(head))) };
+            self.set_head(Self::advance(head, 1));
             Some(item)
         } else {
             None
         }
     }
 
-    pub fn enqueue(&mut self, item: T) -> Result<(), Error> {
-        let n = self.capacity() + 1;
-        let buffer: &mut [T] = unsafe { self.buffer.as_mut() };
+    /// Adds an `item` to the end of the queue
+    ///
+    /// Returns back the `item` if the queue is full
+    pub fn enqueue(&mut self, item: T) -> Result<(), T> {
+        let (head, tail) = self.indices();
 
-        let next_tail = (self.tail + 1) % n;
-        if next_tail != self.head {
+        if Self::length(head, tail) != N {
             // NOTE(ptr::write) the memory slot that we are about to write to is uninitialized. We
             // use `ptr::write` to avoid running `T`'s destructor on the uninitialized memory
-            unsafe { ptr::write(&mut buffer[self.tail], item) }
-            self.tail = next_tail;
+            unsafe { ptr::write(self.buffer_ptr().add(Self::mask(tail)), item) }
+            self.set_tail(Self::advance(tail, 1));
             Ok(())
         } else {
-            Err(Error::Full)
+            Err(item)
+        }
+    }
+
+    /// Copies as many items from the front of `items` into the end of the queue as there is space
+    /// for, and returns the number of items copied
+    pub fn enqueue_slice(&mut self, items: &[T]) -> usize
+    where
+        T: Copy,
+    {
+        let n = cmp::min(items.len(), self.free_space());
+        let (_, tail) = self.indices();
+
+        unsafe { Self::write_slice(self.buffer_ptr(), Self::mask(tail), &items[..n]) }
+        self.set_tail(Self::advance(tail, n));
+        n
+    }
+
+    /// Moves as many items from the front of the queue into `items` as fit, and returns the
+    /// number of items moved
+    pub fn dequeue_into(&mut self, items: &mut [T]) -> usize
+    where
+        T: Copy,
+    {
+        let n = cmp::min(items.len(), self.len());
+        let (head, _) = self.indices();
+
+        unsafe { Self::read_slice(self.buffer_ptr(), Self::mask(head), &mut items[..n]) }
+        self.set_head(Self::advance(head, n));
+        n
+    }
+
+    /// Adds an `item` to the front of the queue
+    ///
+    /// Returns back the `item` if the queue is full
+    pub fn enqueue_front(&mut self, item: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(item);
+        }
+
+        let (head, _) = self.indices();
+        let head = Self::retreat(head);
+
+        // NOTE(ptr::write) the memory slot that we are about to write to is uninitialized. We
+        // use `ptr::write` to avoid running `T`'s destructor on the uninitialized memory
+        unsafe { ptr::write(self.buffer_ptr().add(Self::mask(head)), item) }
+        self.set_head(head);
+        Ok(())
+    }
+
+    /// Removes the item in the back of the queue (i.e. the most recently enqueued one) and
+    /// returns it, or `None` if the queue is empty
+    pub fn dequeue_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let (_, tail) = self.indices();
+        let tail = Self::retreat(tail);
+
+        self.set_tail(tail);
+        Some(unsafe { ptr::read(self.buffer_ptr().add(Self::mask(tail))) })
+    }
+
+    /// Drops all the items in the queue
+    pub fn clear(&mut self) {
+        let (front, back) = self.as_mut_slices();
+        let (front, back) = (front as *mut [T], back as *mut [T]);
+
+        // NOTE(set_head) empty the queue first so that a panicking destructor can't cause a
+        // double drop; the remaining items are leaked instead
+        self.set_head(0);
+        self.set_tail(0);
+        unsafe {
+            ptr::drop_in_place(front);
+            ptr::drop_in_place(back);
+        }
+    }
+
+    /// Rotates the queue `n` places to the left
+    ///
+    /// After the call the item previously at index `n` is in the front of the queue, and the
+    /// first `n` items are in the back of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the queue
+    pub fn rotate_left(&mut self, n: usize) {
+        let len = self.len();
+        assert!(n <= len, "n (is {}) should be <= len (is {})", n, len);
+
+        if n <= len / 2 {
+            for _ in 0..n {
+                unsafe { self.front_to_back() }
+            }
+        } else {
+            for _ in 0..len - n {
+                unsafe { self.back_to_front() }
+            }
         }
     }
 
+    /// Rotates the queue `n` places to the right
+    ///
+    /// After the call the last `n` items are in the front of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the queue
+    pub fn rotate_right(&mut self, n: usize) {
+        let len = self.len();
+        assert!(n <= len, "n (is {}) should be <= len (is {})", n, len);
+
+        self.rotate_left(len - n);
+    }
+
+    /// Swaps the items at indices `i` and `j`
+    ///
+    /// The item at index `0` is the front of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds
+    pub fn swap(&mut self, i: usize, j: usize) {
+        let len = self.len();
+        assert!(i < len, "i (is {}) should be < len (is {})", i, len);
+        assert!(j < len, "j (is {}) should be < len (is {})", j, len);
+
+        let buffer = self.buffer_ptr();
+        unsafe { ptr::swap(buffer.add(self.slot(i)), buffer.add(self.slot(j))) }
+    }
+
+    /// Returns the number of elements in the queue
     pub fn len(&self) -> usize {
-        if self.head > self.tail {
-            self.head - self.tail
+        let (head, tail) = self.indices();
+        Self::length(head, tail)
+    }
+
+    /// Returns `true` if the queue contains no elements
+    pub fn is_empty(&self) -> bool {
+        U::load(&self.head, Ordering::Relaxed) == U::load(&self.tail, Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the queue can't hold any more elements
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Returns the number of elements that can still be enqueued
+    pub fn free_space(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    /// Returns a reference to the item in the front of the queue without dequeuing it, or `None`
+    /// if the queue is empty
+    pub fn peek(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Mutable version of `peek`
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    /// Returns a reference to the item in the back of the queue (i.e. the most recently enqueued
+    /// one), or `None` if the queue is empty
+    pub fn back(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// Mutable version of `back`
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.len().checked_sub(1).and_then(move |i| self.get_mut(i))
+    }
+
+    /// Returns a reference to the item at position `index`, or `None` if `index` is out of
+    /// bounds
+    ///
+    /// The item at index `0` is the front of the queue.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len() {
+            let i = self.slot(index);
+            Some(unsafe { &*self.buffer_ptr().add(i) })
+        } else {
+            None
+        }
+    }
+
+    /// Mutable version of `get`
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len() {
+            let i = self.slot(index);
+            Some(unsafe { &mut *self.buffer_ptr().add(i) })
         } else {
-            self.tail - self.head
+            None
         }
     }
 
+    /// Returns a pair of slices which contain, in order, the contents of the queue
+    ///
+    /// The first slice starts at the front of the queue. The second slice is empty unless the
+    /// contents wrap around the end of the underlying buffer.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let buffer = self.buffer_ptr();
+        let (head, first, second) = self.regions();
+
+        unsafe {
+            (
+                slice::from_raw_parts(buffer.add(head), first),
+                slice::from_raw_parts(buffer, second),
+            )
+        }
+    }
+
+    /// Mutable version of `as_slices`
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let buffer = self.buffer_ptr();
+        let (head, first, second) = self.regions();
+
+        unsafe {
+            (
+                slice::from_raw_parts_mut(buffer.add(head), first),
+                slice::from_raw_parts_mut(buffer, second),
+            )
+        }
+    }
+
+    /// Rearranges the underlying buffer so that the contents of the queue are stored
+    /// contiguously, and returns them as a single slice in front-to-back order
+    ///
+    /// This doesn't move any item if the contents are already contiguous; otherwise it rotates
+    /// the whole underlying buffer in place.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        let (head, first, second) = self.regions();
+
+        if second != 0 {
+            // NOTE(MaybeUninit) the free slots between `tail` and `head` are uninitialized so we
+            // rotate the buffer as a slice of `MaybeUninit`s
+            let buffer =
+                unsafe { slice::from_raw_parts_mut(self.buffer_ptr() as *mut MaybeUninit<T>, N) };
+            buffer.rotate_left(head);
+
+            let len = first + second;
+            self.set_head(0);
+            self.set_tail(len);
+        }
+
+        self.as_mut_slices().0
+    }
+
     /// Iterates from the front of the queue to the back
-    pub fn iter(&self) -> Iter<T, A> {
+    pub fn iter(&self) -> Iter<'_, T, N, U> {
         Iter {
             rb: self,
             index: 0,
@@ -90,7 +358,7 @@ where
     }
 
     /// Mutable version of `iter`
-    pub fn iter_mut(&mut self) -> IterMut<T, A> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, N, U> {
         let len = self.len();
         IterMut {
             rb: self,
@@ -98,11 +366,183 @@ where
             len,
         }
     }
+
+    fn buffer_ptr(&self) -> *mut T {
+        self.buffer.get() as *mut T
+    }
+
+    fn indices(&self) -> (usize, usize) {
+        (
+            U::load(&self.head, Ordering::Relaxed),
+            U::load(&self.tail, Ordering::Relaxed),
+        )
+    }
+
+    // NOTE(Relaxed) a `&mut` reference to the ring buffer means that it hasn't been split, or
+    // that the end points are gone
+    fn set_head(&mut self, head: usize) {
+        U::store(&self.head, head, Ordering::Relaxed)
+    }
+
+    fn set_tail(&mut self, tail: usize) {
+        U::store(&self.tail, tail, Ordering::Relaxed)
+    }
+
+    // Maps a `head` or `tail` index to a slot of the buffer
+    fn mask(index: usize) -> usize {
+        if Self::POW2 {
+            index & (N - 1)
+        } else if index >= N {
+            index - N
+        } else {
+            index
+        }
+    }
+
+    // Moves a `head` or `tail` index `n` slots forward
+    //
+    // NOTE `n` must not be greater than `N`
+    fn advance(index: usize, n: usize) -> usize {
+        if Self::POW2 {
+            index.wrapping_add(n) & U::MAX
+        } else if index + n >= 2 * N {
+            index + n - 2 * N
+        } else {
+            index + n
+        }
+    }
+
+    // Moves a `head` or `tail` index one slot backwards
+    fn retreat(index: usize) -> usize {
+        if Self::POW2 {
+            index.wrapping_sub(1) & U::MAX
+        } else if index == 0 {
+            2 * N - 1
+        } else {
+            index - 1
+        }
+    }
+
+    // Number of items stored between the `head` and `tail` indices
+    fn length(head: usize, tail: usize) -> usize {
+        if Self::POW2 {
+            tail.wrapping_sub(head) & U::MAX
+        } else if head > tail {
+            // `tail` has wrapped around `2 * N`
+            2 * N - head + tail
+        } else {
+            tail - head
+        }
+    }
+
+    // Returns the slot of the front of the queue, and the lengths of the two contiguous regions
+    // that hold the contents of the queue: the one that starts at that slot, and the one that
+    // starts at the beginning of the buffer
+    fn regions(&self) -> (usize, usize, usize) {
+        let (head, tail) = self.indices();
+        let len = Self::length(head, tail);
+        let head = Self::mask(head);
+        let first = cmp::min(len, N - head);
+
+        (head, first, len - first)
+    }
+
+    // Copies `items` into the buffer starting at slot `tail`, wrapping around the end of the
+    // buffer if necessary
+    //
+    // NOTE the destination slots must be free
+    unsafe fn write_slice(buffer: *mut T, tail: usize, items: &[T])
+    where
+        T: Copy,
+    {
+        let first = cmp::min(items.len(), N - tail);
+        ptr::copy_nonoverlapping(items.as_ptr(), buffer.add(tail), first);
+        ptr::copy_nonoverlapping(items.as_ptr().add(first), buffer, items.len() - first);
+    }
+
+    // Copies the items stored in the buffer starting at slot `head` into `items`, wrapping
+    // around the end of the buffer if necessary
+    //
+    // NOTE the source slots must be initialized
+    unsafe fn read_slice(buffer: *const T, head: usize, items: &mut [T])
+    where
+        T: Copy,
+    {
+        let first = cmp::min(items.len(), N - head);
+        ptr::copy_nonoverlapping(buffer.add(head), items.as_mut_ptr(), first);
+        ptr::copy_nonoverlapping(buffer, items.as_mut_ptr().add(first), items.len() - first);
+    }
+
+    // Maps the position `index`, relative to the front of the queue, to a slot of the buffer
+    fn slot(&self, index: usize) -> usize {
+        Self::mask(Self::advance(U::load(&self.head, Ordering::Relaxed), index))
+    }
+
+    // Moves the item in the front of the queue to the back of the queue
+    //
+    // NOTE the queue must not be empty
+    unsafe fn front_to_back(&mut self) {
+        let buffer = self.buffer_ptr();
+        let (head, tail) = self.indices();
+
+        // NOTE(ptr::copy) the source and destination slots are the same when the queue is full
+        ptr::copy(buffer.add(Self::mask(head)), buffer.add(Self::mask(tail)), 1);
+        self.set_head(Self::advance(head, 1));
+        self.set_tail(Self::advance(tail, 1));
+    }
+
+    // Moves the item in the back of the queue to the front of the queue
+    //
+    // NOTE the queue must not be empty
+    unsafe fn back_to_front(&mut self) {
+        let buffer = self.buffer_ptr();
+        let (head, tail) = self.indices();
+        let (head, tail) = (Self::retreat(head), Self::retreat(tail));
+
+        // NOTE(ptr::copy) the source and destination slots are the same when the queue is full
+        ptr::copy(buffer.add(Self::mask(tail)), buffer.add(Self::mask(head)), 1);
+        self.set_head(head);
+        self.set_tail(tail);
+    }
 }
 
-impl<T, A> Drop for RingBuffer<T, A>
+impl<T, const N: usize, U> Clone for RingBuffer<T, N, U>
 where
-    A: Unsize<[T]>,
+    T: Clone,
+    U: Uxx,
+{
+    fn clone(&self) -> Self {
+        let mut rb = RingBuffer::new();
+        for item in self {
+            // NOTE(unsafe) `self` and `rb` have the same capacity
+            unsafe { rb.enqueue(item.clone()).unwrap_unchecked() }
+        }
+        rb
+    }
+}
+
+impl<T, const N: usize, U> fmt::Debug for RingBuffer<T, N, U>
+where
+    T: fmt::Debug,
+    U: Uxx,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize, U> Default for RingBuffer<T, N, U>
+where
+    U: Uxx,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize, U> Drop for RingBuffer<T, N, U>
+where
+    U: Uxx,
 {
     fn drop(&mut self) {
         for item in self {
@@ -113,97 +553,271 @@ where
     }
 }
 
-impl<'a, T, A> IntoIterator for &'a RingBuffer<T, A>
+/// Two ring buffers are equal if they contain the same elements in the same front-to-back order,
+/// regardless of where those elements are located in the underlying storage
+impl<A, B, const N1: usize, const N2: usize, U1, U2> PartialEq<RingBuffer<B, N2, U2>>
+    for RingBuffer<A, N1, U1>
+where
+    A: PartialEq<B>,
+    U1: Uxx,
+    U2: Uxx,
+{
+    fn eq(&self, other: &RingBuffer<B, N2, U2>) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<T, const N: usize, U> Eq for RingBuffer<T, N, U>
+where
+    T: Eq,
+    U: Uxx,
+{
+}
+
+impl<T, const N1: usize, const N2: usize, U1, U2> PartialOrd<RingBuffer<T, N2, U2>>
+    for RingBuffer<T, N1, U1>
+where
+    T: PartialOrd,
+    U1: Uxx,
+    U2: Uxx,
+{
+    fn partial_cmp(&self, other: &RingBuffer<T, N2, U2>) -> Option<cmp::Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T, const N: usize, U> Ord for RingBuffer<T, N, U>
+where
+    T: Ord,
+    U: Uxx,
+{
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T, const N: usize, U> Hash for RingBuffer<T, N, U>
+where
+    T: Hash,
+    U: Uxx,
+{
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        // NOTE same scheme as slices: length prefix followed by the elements
+        state.write_usize(self.len());
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+/// # Panics
+///
+/// Panics if `index` is out of bounds
+impl<T, const N: usize, U> ops::Index<usize> for RingBuffer<T, N, U>
+where
+    U: Uxx,
+{
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len();
+        match self.get(index) {
+            Some(item) => item,
+            None => panic!("index out of bounds: the len is {} but the index is {}", len, index),
+        }
+    }
+}
+
+/// # Panics
+///
+/// Panics if `index` is out of bounds
+impl<T, const N: usize, U> ops::IndexMut<usize> for RingBuffer<T, N, U>
+where
+    U: Uxx,
+{
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len();
+        match self.get_mut(index) {
+            Some(item) => item,
+            None => panic!("index out of bounds: the len is {} but the index is {}", len, index),
+        }
+    }
+}
+
+impl<'a, T, const N: usize, U> IntoIterator for &'a RingBuffer<T, N, U>
 where
-    A: Unsize<[T]>,
+    U: Uxx,
 {
     type Item = &'a T;
-    type IntoIter = Iter<'a, T, A>;
+    type IntoIter = Iter<'a, T, N, U>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-impl<'a, T, A> IntoIterator for &'a mut RingBuffer<T, A>
+impl<'a, T, const N: usize, U> IntoIterator for &'a mut RingBuffer<T, N, U>
 where
-    A: Unsize<[T]>,
+    U: Uxx,
 {
     type Item = &'a mut T;
-    type IntoIter = IterMut<'a, T, A>;
+    type IntoIter = IterMut<'a, T, N, U>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
     }
 }
 
-pub struct Iter<'a, T, A>
+pub struct Iter<'a, T, const N: usize, U = usize>
 where
-    A: Unsize<[T]> + 'a,
-    T: 'a,
+    U: Uxx,
 {
-    rb: &'a RingBuffer<T, A>,
+    rb: &'a RingBuffer<T, N, U>,
     index: usize,
     len: usize,
 }
 
-pub struct IterMut<'a, T, A>
+pub struct IterMut<'a, T, const N: usize, U = usize>
 where
-    A: Unsize<[T]> + 'a,
-    T: 'a,
+    U: Uxx,
 {
-    rb: &'a mut RingBuffer<T, A>,
+    rb: &'a mut RingBuffer<T, N, U>,
     index: usize,
     len: usize,
 }
 
-impl<'a, T, A> Iterator for Iter<'a, T, A>
+impl<'a, T, const N: usize, U> Iterator for Iter<'a, T, N, U>
 where
-    A: Unsize<[T]> + 'a,
-    T: 'a,
+    U: Uxx,
 {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<&'a T> {
         if self.index < self.len {
-            let buffer: &[T] = unsafe { self.rb.buffer.as_ref() };
-            let ptr = buffer.as_ptr();
-            let i = (self.rb.head + self.index) % (self.rb.capacity() + 1);
+            let i = self.rb.slot(self.index);
             self.index += 1;
-            Some(unsafe { &*ptr.offset(i as isize) })
+            Some(unsafe { &*self.rb.buffer_ptr().add(i) })
         } else {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T, const N: usize, U> DoubleEndedIterator for Iter<'a, T, N, U>
+where
+    U: Uxx,
+{
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.index < self.len {
+            self.len -= 1;
+            let i = self.rb.slot(self.len);
+            Some(unsafe { &*self.rb.buffer_ptr().add(i) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, const N: usize, U> ExactSizeIterator for Iter<'a, T, N, U>
+where
+    U: Uxx,
+{
 }
 
-impl<'a, T, A> Iterator for IterMut<'a, T, A>
+impl<'a, T, const N: usize, U> FusedIterator for Iter<'a, T, N, U>
 where
-    A: Unsize<[T]> + 'a,
-    T: 'a,
+    U: Uxx,
+{
+}
+
+impl<'a, T, const N: usize, U> Iterator for IterMut<'a, T, N, U>
+where
+    U: Uxx,
 {
     type Item = &'a mut T;
 
     fn next(&mut self) -> Option<&'a mut T> {
         if self.index < self.len {
-            let capacity = self.rb.capacity() + 1;
-            let buffer: &mut [T] = unsafe { self.rb.buffer.as_mut() };
-            let ptr: *mut T = buffer.as_mut_ptr();
-            let i = (self.rb.head + self.index) % capacity;
+            let i = self.rb.slot(self.index);
             self.index += 1;
-            Some(unsafe { &mut *ptr.offset(i as isize) })
+            Some(unsafe { &mut *self.rb.buffer_ptr().add(i) })
         } else {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T, const N: usize, U> DoubleEndedIterator for IterMut<'a, T, N, U>
+where
+    U: Uxx,
+{
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.index < self.len {
+            self.len -= 1;
+            let i = self.rb.slot(self.len);
+            Some(unsafe { &mut *self.rb.buffer_ptr().add(i) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, const N: usize, U> ExactSizeIterator for IterMut<'a, T, N, U>
+where
+    U: Uxx,
+{
+}
+
+impl<'a, T, const N: usize, U> FusedIterator for IterMut<'a, T, N, U>
+where
+    U: Uxx,
+{
 }
 
 #[cfg(test)]
 mod tests {
+    use core::cmp;
+    use core::hash::{Hash, Hasher};
+    use std;
+
+    use ring_buffer::Uxx;
     use RingBuffer;
 
+    #[test]
+    fn deque() {
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
+
+        rb.enqueue(1).unwrap();
+        rb.enqueue_front(0).unwrap();
+        rb.enqueue(2).unwrap();
+        rb.enqueue(3).unwrap();
+
+        assert_eq!(rb.enqueue_front(4), Err(4));
+        assert!(rb.iter().eq(&[0, 1, 2, 3]));
+
+        assert_eq!(rb.dequeue_back(), Some(3));
+        assert_eq!(rb.dequeue_back(), Some(2));
+        assert_eq!(rb.dequeue_back(), Some(1));
+        assert_eq!(rb.dequeue_back(), Some(0));
+        assert_eq!(rb.dequeue_back(), None);
+    }
+
     #[test]
     fn drop() {
+        #[derive(Debug)]
         struct Droppable;
         impl Droppable {
             fn new() -> Self {
@@ -225,7 +839,7 @@ mod tests {
 
 
         {
-            let mut v: RingBuffer<Droppable, [Droppable; 4]> = RingBuffer::new();
+            let mut v: RingBuffer<Droppable, 4> = RingBuffer::new();
             v.enqueue(Droppable::new()).unwrap();
             v.enqueue(Droppable::new()).unwrap();
             v.dequeue().unwrap();
@@ -234,28 +848,284 @@ mod tests {
         assert_eq!(unsafe { COUNT }, 0);
 
         {
-            let mut v: RingBuffer<Droppable, [Droppable; 4]> = RingBuffer::new();
+            let mut v: RingBuffer<Droppable, 4> = RingBuffer::new();
+            v.enqueue(Droppable::new()).unwrap();
+            v.enqueue(Droppable::new()).unwrap();
+        }
+
+        assert_eq!(unsafe { COUNT }, 0);
+
+        {
+            let mut v: RingBuffer<Droppable, 3> = RingBuffer::new();
+            v.enqueue(Droppable::new()).unwrap();
+            v.enqueue(Droppable::new()).unwrap();
+            v.dequeue().unwrap();
+            // wraps around the end of the buffer
+            v.enqueue(Droppable::new()).unwrap();
             v.enqueue(Droppable::new()).unwrap();
+
+            assert_eq!(unsafe { COUNT }, 3);
+
+            v.clear();
+
+            assert_eq!(unsafe { COUNT }, 0);
+            assert!(v.is_empty());
+
             v.enqueue(Droppable::new()).unwrap();
         }
 
         assert_eq!(unsafe { COUNT }, 0);
     }
 
+    #[test]
+    fn as_slices() {
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
+
+        assert_eq!(rb.as_slices(), (&[][..], &[][..]));
+
+        rb.enqueue(0).unwrap();
+        rb.enqueue(1).unwrap();
+        rb.enqueue(2).unwrap();
+
+        assert_eq!(rb.as_slices(), (&[0, 1, 2][..], &[][..]));
+
+        rb.dequeue().unwrap();
+        rb.dequeue().unwrap();
+        rb.enqueue(3).unwrap();
+        rb.enqueue(4).unwrap();
+
+        assert_eq!(rb.as_slices(), (&[2, 3][..], &[4][..]));
+
+        {
+            let (a, b) = rb.as_mut_slices();
+            a[0] = 5;
+            b[0] = 6;
+        }
+
+        assert!(rb.iter().eq(&[5, 3, 6]));
+    }
+
+    #[test]
+    fn clone() {
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
+
+        rb.enqueue(0).unwrap();
+        rb.dequeue().unwrap();
+        rb.enqueue(1).unwrap();
+        rb.enqueue(2).unwrap();
+        rb.enqueue(3).unwrap();
+
+        let mut clone = rb.clone();
+
+        assert_eq!(clone.dequeue(), Some(1));
+        assert_eq!(clone.dequeue(), Some(2));
+        assert_eq!(clone.dequeue(), Some(3));
+        assert_eq!(clone.dequeue(), None);
+        assert_eq!(rb.len(), 3);
+    }
+
+    #[test]
+    fn cmp() {
+        let mut a: RingBuffer<i32, 4> = RingBuffer::new();
+        let mut b: RingBuffer<i32, 8> = RingBuffer::new();
+
+        a.enqueue(0).unwrap();
+        a.enqueue(1).unwrap();
+        b.enqueue(0).unwrap();
+        b.enqueue(2).unwrap();
+
+        assert!(a < b);
+
+        b.dequeue().unwrap();
+
+        assert!(a < b);
+        assert_eq!(a.cmp(&a.clone()), cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn bulk() {
+        let mut rb: RingBuffer<u8, 8> = RingBuffer::new();
+        let mut buffer = [0; 8];
+
+        assert_eq!(rb.enqueue_slice(&[0, 1, 2, 3, 4]), 5);
+        assert_eq!(rb.dequeue_into(&mut buffer[..3]), 3);
+        assert_eq!(buffer[..3], [0, 1, 2]);
+
+        // wraps around the end of the buffer
+        assert_eq!(rb.enqueue_slice(&[5, 6, 7, 8, 9, 10, 11]), 6);
+        assert!(rb.iter().eq(&[3, 4, 5, 6, 7, 8, 9, 10]));
+
+        assert_eq!(rb.enqueue_slice(&[12]), 0);
+
+        assert_eq!(rb.dequeue_into(&mut buffer), 8);
+        assert_eq!(buffer, [3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(rb.dequeue_into(&mut buffer), 0);
+        assert!(rb.is_empty());
+    }
+
+    #[test]
+    fn debug() {
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
+
+        rb.enqueue(0).unwrap();
+        rb.enqueue(1).unwrap();
+        rb.dequeue().unwrap();
+        rb.enqueue(2).unwrap();
+        rb.enqueue(3).unwrap();
+
+        assert_eq!(std::format!("{:?}", rb), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn eq() {
+        let mut a: RingBuffer<i32, 4> = RingBuffer::new();
+        let mut b: RingBuffer<i32, 4> = RingBuffer::new();
+        let mut c: RingBuffer<i32, 8> = RingBuffer::new();
+
+        // same contents, different wrap positions
+        a.enqueue(0).unwrap();
+        a.enqueue(1).unwrap();
+        a.enqueue(2).unwrap();
+        a.dequeue().unwrap();
+        a.dequeue().unwrap();
+        a.enqueue(3).unwrap();
+        a.enqueue(4).unwrap();
+
+        b.enqueue(2).unwrap();
+        b.enqueue(3).unwrap();
+        b.enqueue(4).unwrap();
+
+        c.enqueue(2).unwrap();
+        c.enqueue(3).unwrap();
+        c.enqueue(4).unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(a, c);
+
+        c.dequeue().unwrap();
+
+        assert_ne!(a, c);
+    }
+
     #[test]
     fn full() {
-        let mut rb: RingBuffer<i32, [i32; 4]> = RingBuffer::new();
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
+
+        assert_eq!(rb.capacity(), 4);
+
+        rb.enqueue(0).unwrap();
+        rb.enqueue(1).unwrap();
+        rb.enqueue(2).unwrap();
+        rb.enqueue(3).unwrap();
+
+        assert_eq!(rb.enqueue(4), Err(4));
+
+        // not a power of two
+        let mut rb: RingBuffer<i32, 3> = RingBuffer::new();
+
+        assert_eq!(rb.capacity(), 3);
+
+        rb.enqueue(0).unwrap();
+        rb.enqueue(1).unwrap();
+        rb.enqueue(2).unwrap();
+
+        assert_eq!(rb.enqueue(3), Err(3));
+    }
+
+    #[test]
+    fn hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash<T: Hash>(t: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let mut a: RingBuffer<i32, 4> = RingBuffer::new();
+        let mut b: RingBuffer<i32, 4> = RingBuffer::new();
+
+        a.enqueue(0).unwrap();
+        a.enqueue(1).unwrap();
+        a.dequeue().unwrap();
+        a.enqueue(2).unwrap();
+        a.enqueue(3).unwrap();
+
+        b.enqueue(1).unwrap();
+        b.enqueue(2).unwrap();
+        b.enqueue(3).unwrap();
+
+        assert_eq!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    fn get() {
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
 
         rb.enqueue(0).unwrap();
         rb.enqueue(1).unwrap();
+        rb.dequeue().unwrap();
         rb.enqueue(2).unwrap();
+        rb.enqueue(3).unwrap();
+
+        assert_eq!(rb.get(0), Some(&1));
+        assert_eq!(rb.get(2), Some(&3));
+        assert_eq!(rb.get(3), None);
+
+        *rb.get_mut(2).unwrap() = 4;
+        rb[0] = 5;
 
-        assert!(rb.enqueue(3).is_err());
+        assert_eq!(rb[0], 5);
+        assert_eq!(rb[1], 2);
+        assert_eq!(rb[2], 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
+
+        rb.enqueue(0).unwrap();
+
+        let _ = rb[1];
+    }
+
+    #[test]
+    fn index_type() {
+        use core::mem;
+
+        assert_eq!(mem::size_of::<RingBuffer<u8, 16, u8>>(), 18);
+        assert_eq!(mem::size_of::<RingBuffer<u8, 16, u16>>(), 20);
+
+        // free-running `u8` indices
+        let mut rb: RingBuffer<i32, 4, u8> = RingBuffer::new();
+
+        for i in 0..1_000 {
+            rb.enqueue(i).unwrap();
+            rb.enqueue(i + 1).unwrap();
+            assert_eq!(rb.len(), 2);
+            assert_eq!(rb.dequeue(), Some(i));
+            assert_eq!(rb.dequeue(), Some(i + 1));
+        }
+
+        assert_eq!(rb.enqueue_slice(&[0, 1, 2, 3]), 4);
+        assert!(rb.is_full());
+        assert_eq!(rb.enqueue(4), Err(4));
+
+        // `u8` indices that wrap around at `2 * N`
+        let mut rb: RingBuffer<i32, 100, u8> = RingBuffer::new();
+
+        assert_eq!(rb.capacity(), 100);
+
+        for i in 0..1_000 {
+            rb.enqueue(i).unwrap();
+            assert_eq!(rb.dequeue(), Some(i));
+        }
     }
 
     #[test]
     fn iter() {
-        let mut rb: RingBuffer<i32, [i32; 4]> = RingBuffer::new();
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
 
         rb.enqueue(0).unwrap();
         rb.enqueue(1).unwrap();
@@ -271,7 +1141,7 @@ mod tests {
 
     #[test]
     fn iter_mut() {
-        let mut rb: RingBuffer<i32, [i32; 4]> = RingBuffer::new();
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
 
         rb.enqueue(0).unwrap();
         rb.enqueue(1).unwrap();
@@ -285,9 +1155,291 @@ mod tests {
         assert_eq!(items.next(), None);
     }
 
+    #[test]
+    fn iter_rev() {
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
+
+        rb.enqueue(0).unwrap();
+        rb.enqueue(1).unwrap();
+        rb.dequeue().unwrap();
+        rb.enqueue(2).unwrap();
+        rb.enqueue(3).unwrap();
+
+        let mut items = rb.iter();
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items.next_back(), Some(&3));
+        assert_eq!(items.next(), Some(&1));
+        assert_eq!(items.len(), 1);
+        assert_eq!(items.next_back(), Some(&2));
+        assert_eq!(items.next_back(), None);
+        assert_eq!(items.next(), None);
+
+        for item in rb.iter_mut().rev().take(2) {
+            *item *= 10;
+        }
+
+        assert!(rb.iter().eq(&[1, 20, 30]));
+    }
+
+    #[test]
+    fn len() {
+        let mut rb: RingBuffer<i32, 3> = RingBuffer::new();
+
+        assert_eq!(rb.len(), 0);
+        assert!(rb.is_empty());
+        assert_eq!(rb.free_space(), 3);
+
+        for i in 0..4 {
+            rb.enqueue(i).unwrap();
+            rb.dequeue().unwrap();
+        }
+        rb.enqueue(4).unwrap();
+        rb.enqueue(5).unwrap();
+        rb.enqueue(6).unwrap();
+
+        // head = 4, tail = 1
+        assert_eq!(rb.len(), 3);
+        assert!(rb.is_full());
+        assert_eq!(rb.free_space(), 0);
+
+        rb.dequeue().unwrap();
+
+        // head = 5, tail = 1
+        assert_eq!(rb.len(), 2);
+        assert!(!rb.is_full());
+        assert_eq!(rb.free_space(), 1);
+    }
+
+    #[test]
+    fn make_contiguous() {
+        for start in 0..8 {
+            let mut rb: RingBuffer<i32, 8> = RingBuffer::new();
+
+            for _ in 0..start {
+                rb.enqueue(0).unwrap();
+                rb.dequeue().unwrap();
+            }
+
+            for i in 0..6 {
+                rb.enqueue(i).unwrap();
+            }
+
+            assert_eq!(rb.make_contiguous(), &[0, 1, 2, 3, 4, 5]);
+            assert_eq!(rb.as_slices(), (&[0, 1, 2, 3, 4, 5][..], &[][..]));
+
+            // the queue keeps working after the rearrangement
+            rb.enqueue(6).unwrap();
+            assert_eq!(rb.dequeue(), Some(0));
+            assert!(rb.iter().eq(&[1, 2, 3, 4, 5, 6]));
+        }
+    }
+
+    #[test]
+    fn model() {
+        use core::sync::atomic::{AtomicIsize, Ordering};
+        use std::collections::VecDeque;
+
+        // number of `Tracked` values currently alive
+        static LIVE: AtomicIsize = AtomicIsize::new(0);
+
+        #[derive(Debug)]
+        struct Tracked(u32);
+
+        impl Tracked {
+            fn new(x: u32) -> Self {
+                LIVE.fetch_add(1, Ordering::SeqCst);
+                Tracked(x)
+            }
+        }
+
+        impl Drop for Tracked {
+            fn drop(&mut self) {
+                LIVE.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        // xorshift32; deterministic so that failures are reproducible
+        struct Rng(u32);
+
+        impl Rng {
+            fn next(&mut self) -> u32 {
+                let mut x = self.0;
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                self.0 = x;
+                x
+            }
+        }
+
+        fn check<const N: usize, U>(rng: &mut Rng)
+        where
+            U: Uxx,
+        {
+            let n = RingBuffer::<Tracked, N, U>::new().capacity();
+
+            // start the sequence of operations from every possible wrap position
+            for start in 0..n + 1 {
+                {
+                    let mut rb: RingBuffer<Tracked, N, U> = RingBuffer::new();
+                    let mut model = VecDeque::new();
+
+                    for _ in 0..start {
+                        rb.enqueue(Tracked::new(0)).unwrap();
+                        rb.dequeue().unwrap();
+                    }
+
+                    for _ in 0..1_000 {
+                        let x = rng.next();
+
+                        match x % 4 {
+                            0 => {
+                                if model.len() < n {
+                                    rb.enqueue(Tracked::new(x)).unwrap();
+                                    model.push_back(x);
+                                } else {
+                                    assert!(rb.enqueue(Tracked::new(x)).is_err());
+                                }
+                            }
+                            1 => {
+                                if model.len() < n {
+                                    rb.enqueue_front(Tracked::new(x)).unwrap();
+                                    model.push_front(x);
+                                } else {
+                                    assert!(rb.enqueue_front(Tracked::new(x)).is_err());
+                                }
+                            }
+                            2 => {
+                                assert_eq!(rb.dequeue().map(|t| t.0), model.pop_front());
+                            }
+                            _ => {
+                                assert_eq!(rb.dequeue_back().map(|t| t.0), model.pop_back());
+                            }
+                        }
+
+                        assert_eq!(rb.len(), model.len());
+                        assert_eq!(rb.is_empty(), model.is_empty());
+                        assert_eq!(rb.is_full(), model.len() == n);
+                        assert_eq!(rb.free_space(), n - model.len());
+                        assert!(rb.iter().map(|t| t.0).eq(model.iter().cloned()));
+                        {
+                            let (a, b) = rb.as_slices();
+                            assert!(a.iter().chain(b).map(|t| t.0).eq(model.iter().cloned()));
+                            assert!(!a.is_empty() || b.is_empty());
+                        }
+                        assert!(rb.iter().rev().map(|t| t.0).eq(model.iter().rev().cloned()));
+                        assert_eq!(rb.iter().len(), model.len());
+                        assert_eq!(rb.peek().map(|t| t.0), model.front().cloned());
+                        assert_eq!(rb.back().map(|t| t.0), model.back().cloned());
+
+                        for t in rb.iter_mut() {
+                            t.0 = t.0.wrapping_add(1);
+                        }
+                        for x in model.iter_mut() {
+                            *x = x.wrapping_add(1);
+                        }
+
+                        assert_eq!(LIVE.load(Ordering::SeqCst), model.len() as isize);
+                    }
+                }
+
+                // `Drop` must destroy exactly the items that were still queued
+                assert_eq!(LIVE.load(Ordering::SeqCst), 0);
+            }
+        }
+
+        let mut rng = Rng(0x2545_f491);
+        check::<2, usize>(&mut rng);
+        check::<3, usize>(&mut rng);
+        check::<4, usize>(&mut rng);
+        check::<5, usize>(&mut rng);
+        check::<8, usize>(&mut rng);
+        check::<17, usize>(&mut rng);
+        // the indices wrap around at `u8::MAX` and `u16::MAX`
+        check::<4, u8>(&mut rng);
+        check::<5, u8>(&mut rng);
+        check::<8, u16>(&mut rng);
+    }
+
+    #[test]
+    fn overflow() {
+        // the indices of power of two sized ring buffers wrap around at `usize::MAX`
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
+        *rb.head.get_mut() = usize::MAX - 1;
+        *rb.tail.get_mut() = usize::MAX - 1;
+
+        rb.enqueue(0).unwrap();
+        rb.enqueue(1).unwrap();
+        rb.enqueue(2).unwrap();
+        rb.enqueue_front(-1).unwrap();
+
+        assert!(rb.is_full());
+        assert!(rb.iter().eq(&[-1, 0, 1, 2]));
+        assert_eq!(rb.as_slices(), (&[-1, 0, 1][..], &[2][..]));
+
+        assert_eq!(rb.dequeue(), Some(-1));
+        assert_eq!(rb.dequeue(), Some(0));
+        rb.enqueue(3).unwrap();
+
+        assert_eq!(rb.len(), 3);
+        assert_eq!(rb.make_contiguous(), [1, 2, 3]);
+        assert_eq!(rb.dequeue_back(), Some(3));
+        assert_eq!(rb.dequeue(), Some(1));
+        assert_eq!(rb.dequeue(), Some(2));
+        assert!(rb.is_empty());
+    }
+
+    #[test]
+    fn peek() {
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
+
+        assert_eq!(rb.peek(), None);
+        assert_eq!(rb.back(), None);
+
+        rb.enqueue(0).unwrap();
+        rb.enqueue(1).unwrap();
+
+        assert_eq!(rb.peek(), Some(&0));
+        assert_eq!(rb.back(), Some(&1));
+
+        *rb.peek_mut().unwrap() = 2;
+        *rb.back_mut().unwrap() = 3;
+
+        assert_eq!(rb.dequeue(), Some(2));
+        assert_eq!(rb.dequeue(), Some(3));
+    }
+
+    #[test]
+    fn rotate() {
+        let mut rb: RingBuffer<i32, 8> = RingBuffer::new();
+
+        rb.enqueue(0).unwrap();
+        rb.dequeue().unwrap();
+        for i in 0..7 {
+            rb.enqueue(i).unwrap();
+        }
+
+        rb.rotate_left(2);
+        assert!(rb.iter().eq(&[2, 3, 4, 5, 6, 0, 1]));
+
+        rb.rotate_left(5);
+        assert!(rb.iter().eq(&[0, 1, 2, 3, 4, 5, 6]));
+
+        rb.rotate_right(3);
+        assert!(rb.iter().eq(&[4, 5, 6, 0, 1, 2, 3]));
+
+        rb.rotate_right(7);
+        assert!(rb.iter().eq(&[4, 5, 6, 0, 1, 2, 3]));
+
+        rb.dequeue_back().unwrap();
+        rb.rotate_left(1);
+        assert!(rb.iter().eq(&[5, 6, 0, 1, 2, 4]));
+    }
+
     #[test]
     fn sanity() {
-        let mut rb: RingBuffer<i32, [i32; 4]> = RingBuffer::new();
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
 
         assert_eq!(rb.dequeue(), None);
 
@@ -298,9 +1450,26 @@ mod tests {
         assert_eq!(rb.dequeue(), None);
     }
 
+    #[test]
+    fn swap() {
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
+
+        rb.enqueue(0).unwrap();
+        rb.enqueue(1).unwrap();
+        rb.dequeue().unwrap();
+        rb.enqueue(2).unwrap();
+        rb.enqueue(3).unwrap();
+
+        rb.swap(0, 2);
+        assert!(rb.iter().eq(&[3, 2, 1]));
+
+        rb.swap(1, 1);
+        assert!(rb.iter().eq(&[3, 2, 1]));
+    }
+
     #[test]
     fn wrap_around() {
-        let mut rb: RingBuffer<i32, [i32; 4]> = RingBuffer::new();
+        let mut rb: RingBuffer<i32, 4> = RingBuffer::new();
 
         rb.enqueue(0).unwrap();
         rb.enqueue(1).unwrap();
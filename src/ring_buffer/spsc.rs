@@ -1,106 +1,793 @@
-use core::ptr::{self, Shared};
-use core::marker::Unsize;
+use core::marker::PhantomData;
+use core::ptr::{self, NonNull};
+use core::sync::atomic::Ordering;
+use core::{cmp, ops, slice};
 
-use Error;
-use ring_buffer::RingBuffer;
+use ring_buffer::{RingBuffer, Uxx};
 
-impl<T, A> RingBuffer<T, A>
+impl<T, const N: usize, U> RingBuffer<T, N, U>
 where
-    A: Unsize<[T]>,
+    U: Uxx,
 {
-    /// Splits a statically allocated ring buffer into producer and consumer end points
-    pub fn split(&'static mut self) -> (Producer<T, A>, Consumer<T, A>) {
+    /// Splits a ring buffer into producer and consumer end points
+    ///
+    /// The end points borrow the ring buffer for the lifetime `'rb`. Splitting a `static` ring
+    /// buffer (see `singleton!`) yields end points that can be moved into different execution
+    /// contexts (e.g. an interrupt handler and the main loop); splitting a stack allocated one
+    /// yields end points that can be shared between scoped threads or tasks.
+    pub fn split<'rb>(&'rb mut self) -> (Producer<'rb, T, N, U>, Consumer<'rb, T, N, U>) {
+        let rb = NonNull::from(self);
+
         (
             Producer {
-                rb: unsafe { Shared::new_unchecked(self) },
+                rb,
+                _marker: PhantomData,
             },
             Consumer {
-                rb: unsafe { Shared::new_unchecked(self) },
+                rb,
+                _marker: PhantomData,
             },
         )
     }
+
+    /// Joins the end points returned by `split` back together, giving back exclusive access to
+    /// the ring buffer
+    ///
+    /// The contents of the queue are left as they are. Returns the end points back if they were
+    /// not split from the same ring buffer.
+    #[allow(clippy::type_complexity)]
+    pub fn rejoin<'rb>(
+        producer: Producer<'rb, T, N, U>,
+        consumer: Consumer<'rb, T, N, U>,
+    ) -> Result<&'rb mut Self, (Producer<'rb, T, N, U>, Consumer<'rb, T, N, U>)> {
+        if producer.rb == consumer.rb {
+            // NOTE(unsafe) both end points, which are neither `Clone` nor `Copy`, are consumed so
+            // the `&'rb mut` reference that `split` took is exclusive again
+            Ok(unsafe { &mut *producer.rb.as_ptr() })
+        } else {
+            Err((producer, consumer))
+        }
+    }
 }
 
 /// A ring buffer "consumer"; it can dequeue items from the ring buffer
 // NOTE the consumer semantically owns the `head` pointer of the ring buffer
-pub struct Consumer<T, A>
+pub struct Consumer<'rb, T, const N: usize, U = usize>
 where
-    A: Unsize<[T]>,
+    U: Uxx,
 {
-    // XXX do we need to use `Shared` (for soundness) here?
-    rb: Shared<RingBuffer<T, A>>,
+    rb: NonNull<RingBuffer<T, N, U>>,
+    _marker: PhantomData<&'rb ()>,
 }
 
-impl<T, A> Consumer<T, A>
+// NOTE(unsafe) the consumer only ever touches the `head` index and the slots that the producer
+// has already released to it, so it can be sent to another context as long as the items can
+unsafe impl<'rb, T, const N: usize, U> Send for Consumer<'rb, T, N, U>
 where
-    A: Unsize<[T]>,
+    T: Send,
+    U: Uxx,
 {
+}
+
+impl<'rb, T, const N: usize, U> Consumer<'rb, T, N, U>
+where
+    U: Uxx,
+{
+    /// Returns the item in the front of the queue, or `None` if the queue is empty
     pub fn dequeue(&mut self) -> Option<T> {
-        let rb = unsafe { self.rb.as_mut() };
-        let n = rb.capacity() + 1;
-        let buffer: &[T] = unsafe { rb.buffer.as_ref() };
-
-        // NOTE(volatile) the value of `tail` can change at any time in the context of the consumer
-        // so we inform this to the compiler using a volatile load
-        if rb.head != unsafe { ptr::read_volatile(&rb.tail) } {
-            let item = unsafe { ptr::read(&buffer[rb.head]) };
-            rb.head = (rb.head + 1) % n;
+        let rb = unsafe { self.rb.as_ref() };
+
+        // NOTE(Relaxed) only the consumer modifies `head`
+        let head = U::load(&rb.head, Ordering::Relaxed);
+        // NOTE(Acquire) pairs with the `Release` store in `Producer::enqueue`; this makes the
+        // item written by the producer visible to the consumer
+        let tail = U::load(&rb.tail, Ordering::Acquire);
+
+        if head != tail {
+            let item = unsafe { ptr::read(rb.buffer_ptr().add(RingBuffer::<T, N, U>::mask(head))) };
+            // NOTE(Release) the item must be moved out of the slot before the producer can see
+            // the slot as free and overwrite it
+            U::store(&rb.head, RingBuffer::<T, N, U>::advance(head, 1), Ordering::Release);
             Some(item)
         } else {
             None
         }
     }
+
+    /// Moves as many items from the front of the queue into `items` as fit, and returns the
+    /// number of items moved
+    pub fn dequeue_into(&mut self, items: &mut [T]) -> usize
+    where
+        T: Copy,
+    {
+        let rb = unsafe { self.rb.as_ref() };
+
+        // NOTE(Relaxed) only the consumer modifies `head`
+        let head = U::load(&rb.head, Ordering::Relaxed);
+        // NOTE(Acquire) pairs with the `Release` store in `Producer::enqueue{,_slice}`
+        let tail = U::load(&rb.tail, Ordering::Acquire);
+
+        let n = cmp::min(items.len(), RingBuffer::<T, N, U>::length(head, tail));
+        unsafe {
+            let head = RingBuffer::<T, N, U>::mask(head);
+            RingBuffer::<T, N, U>::read_slice(rb.buffer_ptr(), head, &mut items[..n])
+        }
+        // NOTE(Release) the items must be copied out before the producer can overwrite them
+        U::store(&rb.head, RingBuffer::<T, N, U>::advance(head, n), Ordering::Release);
+        n
+    }
+
+    /// Returns the item in the front of the queue without dequeuing it, or `None` if the queue
+    /// is empty
+    pub fn peek(&self) -> Option<&T> {
+        let rb = unsafe { self.rb.as_ref() };
+
+        // NOTE(Relaxed) only the consumer modifies `head`
+        let head = U::load(&rb.head, Ordering::Relaxed);
+        // NOTE(Acquire) pairs with the `Release` store done by the producer
+        let tail = U::load(&rb.tail, Ordering::Acquire);
+
+        if head != tail {
+            // NOTE(unsafe) the item can only be removed through `&mut self`
+            Some(unsafe { &*rb.buffer_ptr().add(RingBuffer::<T, N, U>::mask(head)) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of items in the queue
+    ///
+    /// The result is a snapshot. The producer may enqueue more items at any time, but the items
+    /// can only be removed by this consumer, so until it dequeues, at least this many items are
+    /// available.
+    pub fn len(&self) -> usize {
+        let rb = unsafe { self.rb.as_ref() };
+
+        // NOTE(Relaxed) only the consumer modifies `head`
+        let head = U::load(&rb.head, Ordering::Relaxed);
+        // NOTE(Acquire) pairs with the `Release` store done by the producer; this makes the
+        // counted items visible to the consumer
+        let tail = U::load(&rb.tail, Ordering::Acquire);
+
+        RingBuffer::<T, N, U>::length(head, tail)
+    }
+
+    /// Returns `true` if the queue contains no items
+    ///
+    /// The result is a snapshot; see `len`.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if there's at least one item to dequeue
+    ///
+    /// The result is a snapshot, but a `true` stays valid until this consumer dequeues: the
+    /// next `dequeue` is guaranteed to return an item.
+    pub fn ready(&self) -> bool {
+        !self.is_empty()
+    }
+
+    /// Returns the maximum number of items the queue can hold
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<'rb, const N: usize, U> Consumer<'rb, u8, N, U>
+where
+    U: Uxx,
+{
+    /// Grants read access to the contiguous region of queued bytes that starts at the front of
+    /// the queue, or returns `None` if the queue is empty
+    ///
+    /// The region ends at the end of the underlying buffer when the queued bytes wrap around it;
+    /// the rest of the bytes become readable once the region has been released. The bytes stay
+    /// in the queue until they are released with `GrantR::release`.
+    pub fn read(&mut self) -> Option<GrantR<'_, N, U>> {
+        let rb = unsafe { self.rb.as_ref() };
+
+        // NOTE(Relaxed) only the consumer modifies `head`
+        let head = U::load(&rb.head, Ordering::Relaxed);
+        // NOTE(Acquire) pairs with the `Release` store done by the producer
+        let tail = U::load(&rb.tail, Ordering::Acquire);
+
+        let len = cmp::min(
+            RingBuffer::<u8, N, U>::length(head, tail),
+            N - RingBuffer::<u8, N, U>::mask(head),
+        );
+
+        if len == 0 {
+            None
+        } else {
+            Some(GrantR { rb, head, len })
+        }
+    }
 }
 
+/// Read access to a contiguous region of bytes queued in a ring buffer
+///
+/// This struct is created by `Consumer::read`. Dropping it without calling `release` leaves the
+/// bytes in the queue.
+pub struct GrantR<'a, const N: usize, U = usize>
+where
+    U: Uxx,
+{
+    rb: &'a RingBuffer<u8, N, U>,
+    head: usize,
+    len: usize,
+}
+
+impl<'a, const N: usize, U> GrantR<'a, N, U>
+where
+    U: Uxx,
+{
+    /// Removes the first `used` bytes of the region from the queue
+    ///
+    /// `used` is clamped to the length of the region.
+    pub fn release(self, used: usize) {
+        let used = cmp::min(used, self.len);
+        // NOTE(Release) the bytes must be read before the producer can overwrite them
+        let head = RingBuffer::<u8, N, U>::advance(self.head, used);
+        U::store(&self.rb.head, head, Ordering::Release);
+    }
+}
+
+impl<'a, const N: usize, U> ops::Deref for GrantR<'a, N, U>
+where
+    U: Uxx,
+{
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        let head = RingBuffer::<u8, N, U>::mask(self.head);
+        unsafe { slice::from_raw_parts(self.rb.buffer_ptr().add(head), self.len) }
+    }
+}
 
 /// A ring buffer "producer"; it can enqueue items into the ring buffer
 // NOTE the producer semantically owns the `tail` pointer of the ring buffer
-pub struct Producer<T, A>
+pub struct Producer<'rb, T, const N: usize, U = usize>
+where
+    U: Uxx,
+{
+    rb: NonNull<RingBuffer<T, N, U>>,
+    _marker: PhantomData<&'rb ()>,
+}
+
+// NOTE(unsafe) the producer only ever touches the `tail` index and the slots that the consumer
+// has already released to it, so it can be sent to another context as long as the items can
+unsafe impl<'rb, T, const N: usize, U> Send for Producer<'rb, T, N, U>
 where
-    A: Unsize<[T]>,
+    T: Send,
+    U: Uxx,
 {
-    // XXX do we need to use `Shared` (for soundness) here?
-    rb: Shared<RingBuffer<T, A>>,
 }
 
-impl<T, A> Producer<T, A>
+impl<'rb, T, const N: usize, U> Producer<'rb, T, N, U>
 where
-    A: Unsize<[T]>,
+    U: Uxx,
 {
-    pub fn enqueue(&mut self, item: T) -> Result<(), Error> {
-        let rb = unsafe { self.rb.as_mut() };
-        let n = rb.capacity() + 1;
-        let buffer: &mut [T] = unsafe { rb.buffer.as_mut() };
+    /// Adds an `item` to the end of the queue
+    ///
+    /// Returns back the `item` if the queue is full
+    pub fn enqueue(&mut self, item: T) -> Result<(), T> {
+        let rb = unsafe { self.rb.as_ref() };
+
+        // NOTE(Relaxed) only the producer modifies `tail`
+        let tail = U::load(&rb.tail, Ordering::Relaxed);
+        // NOTE(Acquire) pairs with the `Release` store in `Consumer::dequeue`; this makes sure
+        // the consumer is done reading the slot before we overwrite it
+        let head = U::load(&rb.head, Ordering::Acquire);
 
-        let next_tail = (rb.tail + 1) % n;
-        // NOTE(volatile) the value of `head` can change at any time in the context of the producer
-        // so we inform this to the compiler using a volatile load
-        if next_tail != unsafe { ptr::read_volatile(&rb.head) } {
+        if RingBuffer::<T, N, U>::length(head, tail) != N {
             // NOTE(ptr::write) the memory slot that we are about to write to is uninitialized. We
             // use `ptr::write` to avoid running `T`'s destructor on the uninitialized memory
-            unsafe { ptr::write(&mut buffer[rb.tail], item) }
-            rb.tail = next_tail;
+            unsafe { ptr::write(rb.buffer_ptr().add(RingBuffer::<T, N, U>::mask(tail)), item) }
+            // NOTE(Release) the item must be written into the slot before the consumer can see it
+            U::store(&rb.tail, RingBuffer::<T, N, U>::advance(tail, 1), Ordering::Release);
             Ok(())
         } else {
-            Err(Error::Full)
+            Err(item)
+        }
+    }
+
+    /// Copies as many items from the front of `items` into the end of the queue as there is space
+    /// for, and returns the number of items copied
+    pub fn enqueue_slice(&mut self, items: &[T]) -> usize
+    where
+        T: Copy,
+    {
+        let rb = unsafe { self.rb.as_ref() };
+
+        // NOTE(Relaxed) only the producer modifies `tail`
+        let tail = U::load(&rb.tail, Ordering::Relaxed);
+        // NOTE(Acquire) pairs with the `Release` store in `Consumer::dequeue{,_into}`
+        let head = U::load(&rb.head, Ordering::Acquire);
+
+        let free = rb.capacity() - RingBuffer::<T, N, U>::length(head, tail);
+        let n = cmp::min(items.len(), free);
+        unsafe {
+            let tail = RingBuffer::<T, N, U>::mask(tail);
+            RingBuffer::<T, N, U>::write_slice(rb.buffer_ptr(), tail, &items[..n])
+        }
+        // NOTE(Release) the items must be written before the consumer can see them
+        U::store(&rb.tail, RingBuffer::<T, N, U>::advance(tail, n), Ordering::Release);
+        n
+    }
+
+    /// Returns the number of items that can still be enqueued
+    ///
+    /// The result is a snapshot. The consumer may free more space at any time, but the space can
+    /// only be taken by this producer, so until it enqueues, at least this many items fit.
+    pub fn free(&self) -> usize {
+        let rb = unsafe { self.rb.as_ref() };
+
+        // NOTE(Relaxed) only the producer modifies `tail`
+        let tail = U::load(&rb.tail, Ordering::Relaxed);
+        // NOTE(Acquire) pairs with the `Release` store done by the consumer; the counted slots
+        // are really free
+        let head = U::load(&rb.head, Ordering::Acquire);
+
+        N - RingBuffer::<T, N, U>::length(head, tail)
+    }
+
+    /// Returns `true` if there's room for at least one more item
+    ///
+    /// The result is a snapshot, but a `true` stays valid until this producer enqueues: the next
+    /// `enqueue` is guaranteed to succeed.
+    pub fn ready(&self) -> bool {
+        self.free() != 0
+    }
+
+    /// Returns the maximum number of items the queue can hold
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<'rb, const N: usize, U> Producer<'rb, u8, N, U>
+where
+    U: Uxx,
+{
+    /// Grants write access to a contiguous region of free space, of at most `max` bytes, that
+    /// starts at the end of the queue
+    ///
+    /// The region stops at the end of the underlying buffer, so it may be smaller than the total
+    /// free space when the free space wraps around it. It holds whatever bytes were left there
+    /// (initially zeros). The bytes written into the region are enqueued when they are committed
+    /// with `GrantW::commit`.
+    ///
+    /// Returns `None` if there's no free space at the end of the queue
+    pub fn grant(&mut self, max: usize) -> Option<GrantW<'_, N, U>> {
+        let rb = unsafe { self.rb.as_ref() };
+
+        // NOTE(Relaxed) only the producer modifies `tail`
+        let tail = U::load(&rb.tail, Ordering::Relaxed);
+        // NOTE(Acquire) pairs with the `Release` store done by the consumer
+        let head = U::load(&rb.head, Ordering::Acquire);
+
+        let free = rb.capacity() - RingBuffer::<u8, N, U>::length(head, tail);
+        let contiguous = cmp::min(free, N - RingBuffer::<u8, N, U>::mask(tail));
+
+        if contiguous == 0 {
+            None
+        } else {
+            // NOTE the buffer is zeroed when the ring buffer is created, so every byte of the
+            // region is initialized
+            let len = cmp::min(max, contiguous);
+            Some(GrantW { rb, tail, len })
         }
     }
 }
 
+/// Write access to a contiguous region of free space in a ring buffer
+///
+/// This struct is created by `Producer::grant`. Dropping it without calling `commit` enqueues
+/// nothing.
+pub struct GrantW<'a, const N: usize, U = usize>
+where
+    U: Uxx,
+{
+    rb: &'a RingBuffer<u8, N, U>,
+    tail: usize,
+    len: usize,
+}
+
+impl<'a, const N: usize, U> GrantW<'a, N, U>
+where
+    U: Uxx,
+{
+    /// Enqueues the first `used` bytes of the region
+    ///
+    /// `used` is clamped to the length of the region.
+    pub fn commit(self, used: usize) {
+        let used = cmp::min(used, self.len);
+        // NOTE(Release) the bytes must be written before the consumer can see them
+        let tail = RingBuffer::<u8, N, U>::advance(self.tail, used);
+        U::store(&self.rb.tail, tail, Ordering::Release);
+    }
+}
+
+impl<'a, const N: usize, U> ops::Deref for GrantW<'a, N, U>
+where
+    U: Uxx,
+{
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        let tail = RingBuffer::<u8, N, U>::mask(self.tail);
+        unsafe { slice::from_raw_parts(self.rb.buffer_ptr().add(tail), self.len) }
+    }
+}
+
+impl<'a, const N: usize, U> ops::DerefMut for GrantW<'a, N, U>
+where
+    U: Uxx,
+{
+    fn deref_mut(&mut self) -> &mut [u8] {
+        let tail = RingBuffer::<u8, N, U>::mask(self.tail);
+        unsafe { slice::from_raw_parts_mut(self.rb.buffer_ptr().add(tail), self.len) }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use core::{cmp, ptr};
+    use std::thread;
+
     use RingBuffer;
 
+    #[test]
+    fn grant() {
+        let mut rb: RingBuffer<u8, 8> = RingBuffer::new();
+        let (mut p, mut c) = rb.split();
+
+        assert!(c.read().is_none());
+
+        {
+            let mut grant = p.grant(4).unwrap();
+            assert_eq!(grant.len(), 4);
+            // never written to
+            assert_eq!(*grant, [0; 4]);
+            grant.copy_from_slice(&[0, 1, 2, 3]);
+            grant.commit(3);
+        }
+
+        {
+            let grant = c.read().unwrap();
+            assert_eq!(*grant, [0, 1, 2]);
+            grant.release(2);
+        }
+
+        assert_eq!(c.dequeue(), Some(2));
+
+        // the free space at the end of the buffer is smaller than requested
+        {
+            let mut grant = p.grant(8).unwrap();
+            assert_eq!(grant.len(), 5);
+            grant.copy_from_slice(&[3, 4, 5, 6, 7]);
+            grant.commit(5);
+        }
+
+        // the rest of the free space is at the start of the buffer
+        {
+            let mut grant = p.grant(8).unwrap();
+            assert_eq!(grant.len(), 3);
+            grant.copy_from_slice(&[8, 9, 10]);
+            grant.commit(3);
+        }
+
+        assert!(p.grant(1).is_none());
+
+        {
+            let grant = c.read().unwrap();
+            assert_eq!(*grant, [3, 4, 5, 6, 7]);
+            grant.release(5);
+        }
+
+        // an aborted grant doesn't enqueue anything
+        {
+            let _grant = p.grant(1).unwrap();
+        }
+
+        {
+            let grant = c.read().unwrap();
+            assert_eq!(*grant, [8, 9, 10]);
+            grant.release(3);
+        }
+
+        assert!(c.read().is_none());
+    }
+
+    #[test]
+    fn grant_threads() {
+        const N: usize = 100_000;
+
+        let mut rb: RingBuffer<u8, 16> = RingBuffer::new();
+        let (mut p, mut c) = rb.split();
+
+        thread::scope(move |s| {
+            s.spawn(move || {
+                let mut i = 0;
+                while i < N {
+                    if let Some(mut grant) = p.grant(5) {
+                        let n = cmp::min(grant.len(), N - i);
+                        for byte in &mut grant[..n] {
+                            *byte = i as u8;
+                            i += 1;
+                        }
+                        grant.commit(n);
+                    }
+                    thread::yield_now();
+                }
+            });
+
+            s.spawn(move || {
+                let mut i = 0;
+                while i < N {
+                    if let Some(grant) = c.read() {
+                        for &byte in grant.iter() {
+                            assert_eq!(byte, i as u8);
+                            i += 1;
+                        }
+                        let n = grant.len();
+                        grant.release(n);
+                    }
+                    thread::yield_now();
+                }
+            });
+        });
+    }
+
+    #[test]
+    fn ready() {
+        let mut rb: RingBuffer<i32, 3> = RingBuffer::new();
+        let (mut p, mut c) = rb.split();
+
+        assert_eq!(p.capacity(), 3);
+        assert_eq!(c.capacity(), 3);
+
+        assert!(p.ready());
+        assert!(!c.ready());
+        assert_eq!(p.free(), 3);
+        assert_eq!(c.len(), 0);
+        assert_eq!(c.peek(), None);
+
+        p.enqueue(0).unwrap();
+        p.enqueue(1).unwrap();
+
+        assert_eq!(p.free(), 1);
+        assert_eq!(c.len(), 2);
+        assert_eq!(c.peek(), Some(&0));
+        // peeking doesn't dequeue
+        assert_eq!(c.peek(), Some(&0));
+
+        p.enqueue(2).unwrap();
+
+        assert!(!p.ready());
+        assert!(c.ready());
+        assert_eq!(p.free(), 0);
+        assert_eq!(c.len(), 3);
+
+        assert_eq!(c.dequeue(), Some(0));
+
+        assert!(p.ready());
+        assert_eq!(p.free(), 1);
+        assert_eq!(c.peek(), Some(&1));
+    }
+
+    #[test]
+    fn ready_threads() {
+        const N: u32 = 100_000;
+
+        let mut rb: RingBuffer<u32, 5> = RingBuffer::new();
+        let (mut p, mut c) = rb.split();
+
+        thread::scope(move |s| {
+            s.spawn(move || {
+                let mut i = 0;
+                while i < N {
+                    // the free space reported by the producer is a lower bound
+                    let free = p.free();
+                    for _ in 0..cmp::min(free, (N - i) as usize) {
+                        assert!(p.ready());
+                        p.enqueue(i).unwrap();
+                        i += 1;
+                    }
+                    thread::yield_now();
+                }
+            });
+
+            s.spawn(move || {
+                let mut i = 0;
+                while i < N {
+                    // the length reported by the consumer is a lower bound
+                    let len = c.len();
+                    for _ in 0..len {
+                        assert!(c.ready());
+                        assert_eq!(c.peek(), Some(&i));
+                        assert_eq!(c.dequeue(), Some(i));
+                        i += 1;
+                    }
+                    thread::yield_now();
+                }
+            });
+        });
+    }
+
+    #[test]
+    fn rejoin() {
+        let mut a: RingBuffer<i32, 4> = RingBuffer::new();
+        let mut b: RingBuffer<i32, 4> = RingBuffer::new();
+
+        let (mut pa, ca) = a.split();
+        let (pb, cb) = b.split();
+
+        pa.enqueue(0).unwrap();
+        pa.enqueue(1).unwrap();
+
+        // mismatched end points are handed back
+        let (pa, cb) = match RingBuffer::rejoin(pa, cb) {
+            Ok(_) => panic!("rejoined end points of different ring buffers"),
+            Err(halves) => halves,
+        };
+        let (pb, ca) = match RingBuffer::rejoin(pb, ca) {
+            Ok(_) => panic!("rejoined end points of different ring buffers"),
+            Err(halves) => halves,
+        };
+
+        assert!(RingBuffer::rejoin(pb, cb).is_ok());
+
+        let a = RingBuffer::rejoin(pa, ca).ok().unwrap();
+
+        assert!(a.iter().eq(&[0, 1]));
+
+        a.clear();
+
+        assert!(a.is_empty());
+
+        // the ring buffer can be split again
+        let (mut p, mut c) = a.split();
+        p.enqueue(2).unwrap();
+        assert_eq!(c.dequeue(), Some(2));
+    }
+
     #[test]
     fn sanity() {
-        static mut RB: RingBuffer<i32, [i32; 2]> = RingBuffer::new();
+        static mut RB: RingBuffer<i32, 2> = RingBuffer::new();
 
-        let (mut p, mut c) = unsafe { RB.split() };
+        let (mut p, mut c) = unsafe { (*ptr::addr_of_mut!(RB)).split() };
 
         assert_eq!(c.dequeue(), None);
 
         p.enqueue(0).unwrap();
+        p.enqueue(1).unwrap();
 
+        assert_eq!(p.enqueue(2), Err(2));
         assert_eq!(c.dequeue(), Some(0));
+
+        // every slot is usable whatever the size of the buffer
+        let mut rb: RingBuffer<i32, 3> = RingBuffer::new();
+        let (mut p, mut c) = rb.split();
+
+        for i in 0..10 {
+            p.enqueue(i).unwrap();
+            p.enqueue(i + 1).unwrap();
+            p.enqueue(i + 2).unwrap();
+
+            assert_eq!(p.enqueue(i + 3), Err(i + 3));
+            assert_eq!(c.dequeue(), Some(i));
+            assert_eq!(c.dequeue(), Some(i + 1));
+            assert_eq!(c.dequeue(), Some(i + 2));
+            assert_eq!(c.dequeue(), None);
+        }
+    }
+
+    #[test]
+    fn threads() {
+        const N: u32 = 100_000;
+
+        let rb = singleton!(: RingBuffer<u32, 8> = RingBuffer::new()).unwrap();
+
+        let (mut p, mut c) = rb.split();
+
+        let producer = thread::spawn(move || {
+            for i in 0..N {
+                while p.enqueue(i).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+
+        for i in 0..N {
+            loop {
+                if let Some(j) = c.dequeue() {
+                    assert_eq!(i, j);
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+
+        producer.join().unwrap();
+
+        assert_eq!(c.dequeue(), None);
+    }
+
+    #[test]
+    fn bulk() {
+        const N: u32 = 100_000;
+
+        // NOTE not a power of two
+        let mut rb: RingBuffer<u32, 13> = RingBuffer::new();
+
+        let (mut p, mut c) = rb.split();
+
+        thread::scope(move |s| {
+            s.spawn(move || {
+                let mut i = 0;
+                while i < N {
+                    let chunk = [i, i + 1, i + 2, i + 3, i + 4];
+                    let end = cmp::min(chunk.len(), (N - i) as usize);
+                    let mut sent = 0;
+                    while sent < end {
+                        sent += p.enqueue_slice(&chunk[sent..end]);
+                        thread::yield_now();
+                    }
+                    i += end as u32;
+                }
+            });
+
+            s.spawn(move || {
+                let mut buffer = [0; 7];
+                let mut i = 0;
+                while i < N {
+                    let n = c.dequeue_into(&mut buffer);
+                    for &j in &buffer[..n] {
+                        assert_eq!(i, j);
+                        i += 1;
+                    }
+                    thread::yield_now();
+                }
+            });
+        });
+    }
+
+    #[test]
+    fn scoped() {
+        const N: u32 = 100_000;
+
+        // NOTE not a power of two, and narrow indices
+        let mut rb: RingBuffer<u32, 7, u8> = RingBuffer::new();
+
+        {
+            let (mut p, mut c) = rb.split();
+
+            thread::scope(move |s| {
+                s.spawn(move || {
+                    for i in 0..N {
+                        while p.enqueue(i).is_err() {
+                            thread::yield_now();
+                        }
+                    }
+                });
+
+                s.spawn(move || {
+                    for i in 0..N {
+                        loop {
+                            if let Some(j) = c.dequeue() {
+                                assert_eq!(i, j);
+                                break;
+                            }
+                            thread::yield_now();
+                        }
+                    }
+                });
+            });
+        }
+
+        assert!(rb.is_empty());
+        rb.enqueue(0).unwrap();
+        assert_eq!(rb.dequeue(), Some(0));
     }
 }
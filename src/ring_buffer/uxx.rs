@@ -0,0 +1,124 @@
+#[cfg(target_arch = "msp430")]
+use core::cell::UnsafeCell;
+#[cfg(target_arch = "msp430")]
+use core::ptr;
+#[cfg(not(target_arch = "msp430"))]
+use core::sync::atomic::{AtomicU16, AtomicU8, AtomicUsize};
+#[cfg(target_arch = "msp430")]
+use core::sync::atomic::compiler_fence;
+use core::sync::atomic::Ordering;
+
+/// Types that can be used as the `head` and `tail` indices of a `RingBuffer`
+///
+/// A smaller index type saves RAM on small queues and keeps the index accesses atomic on 8-bit and
+/// 16-bit targets. This trait is sealed: it's implemented for `u8`, `u16` and `usize` and can't be
+/// implemented outside this crate.
+pub trait Uxx: Sealed {}
+
+impl Uxx for u8 {}
+impl Uxx for u16 {}
+impl Uxx for usize {}
+
+// NOTE(Sealed) public but unnameable outside this crate; it carries the operations `RingBuffer`
+// needs so that they don't leak into the public API
+pub trait Sealed {
+    // atomic version of the index type
+    type Atomic;
+
+    // NOTE(declare_interior_mutable_const) only ever used to initialize the indices of a new
+    // ring buffer; each use is a fresh value
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: Self::Atomic;
+
+    // largest value the index type can hold
+    const MAX: usize;
+
+    fn load(atomic: &Self::Atomic, order: Ordering) -> usize;
+
+    // NOTE `value` must not be greater than `MAX`
+    fn store(atomic: &Self::Atomic, value: usize, order: Ordering);
+}
+
+macro_rules! sealed {
+    ($($uxx:ty => $atomic:ty,)+) => {
+        $(
+            impl Sealed for $uxx {
+                type Atomic = $atomic;
+
+                #[allow(clippy::declare_interior_mutable_const)]
+                const ZERO: $atomic = <$atomic>::new(0);
+
+                const MAX: usize = <$uxx>::MAX as usize;
+
+                #[inline(always)]
+                fn load(atomic: &$atomic, order: Ordering) -> usize {
+                    atomic.load(order) as usize
+                }
+
+                #[inline(always)]
+                fn store(atomic: &$atomic, value: usize, order: Ordering) {
+                    atomic.store(value as $uxx, order)
+                }
+            }
+        )+
+    }
+}
+
+#[cfg(not(target_arch = "msp430"))]
+sealed! {
+    u8 => AtomicU8,
+    u16 => AtomicU16,
+    usize => AtomicUsize,
+}
+
+// NOTE(msp430) the one target that has no atomic types at all, not even with only loads and
+// stores. It's single-core and its 8-bit and 16-bit (pointer sized) loads and stores are single
+// instructions, so the indices are accessed with volatile operations plus compiler fences instead
+#[cfg(target_arch = "msp430")]
+sealed! {
+    u8 => Volatile<u8>,
+    u16 => Volatile<u16>,
+    usize => Volatile<usize>,
+}
+
+// stand-in for an atomic integer on targets without atomics
+#[cfg(target_arch = "msp430")]
+pub struct Volatile<T> {
+    value: UnsafeCell<T>,
+}
+
+#[cfg(target_arch = "msp430")]
+impl<T> Volatile<T>
+where
+    T: Copy,
+{
+    const fn new(value: T) -> Self {
+        Volatile {
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    #[inline(always)]
+    fn load(&self, order: Ordering) -> T {
+        let value = unsafe { ptr::read_volatile(self.value.get()) };
+        if order != Ordering::Relaxed {
+            // NOTE(compiler_fence) keeps later memory accesses after the load
+            compiler_fence(Ordering::Acquire);
+        }
+        value
+    }
+
+    #[inline(always)]
+    fn store(&self, value: T, order: Ordering) {
+        if order != Ordering::Relaxed {
+            // NOTE(compiler_fence) keeps earlier memory accesses before the store
+            compiler_fence(Ordering::Release);
+        }
+        unsafe { ptr::write_volatile(self.value.get(), value) }
+    }
+}
@@ -0,0 +1,112 @@
+/// Creates a `static` variable and returns a `&'static mut` reference to it, exactly once
+///
+/// `singleton!(: $ty = $expr)` evaluates to `Option<&'static mut $ty>`: the first time the
+/// expansion is reached it moves the value of `$expr` into a `static` and returns `Some` reference
+/// to it; later executions of the same expansion return `None` and don't evaluate `$expr`. An
+/// atomic flag guards the `static`, so this is safe to use from several threads or interrupt
+/// handlers.
+///
+/// This is the safe way to place a collection in static memory, e.g. to `split` a `RingBuffer`
+/// into end points that can be moved into an interrupt handler.
+///
+/// The flag is claimed with an atomic swap, so this macro is only available on targets with
+/// compare-and-swap (`target_has_atomic = "8"`); it's not available on ARMv6-M (Cortex-M0). On
+/// those targets use a `static mut` and create the reference in a critical section instead.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use(singleton)]
+/// extern crate heapless;
+///
+/// use heapless::RingBuffer;
+///
+/// fn main() {
+///     let rb: &'static mut RingBuffer<u8, 64> =
+///         singleton!(: RingBuffer<u8, 64> = RingBuffer::new()).unwrap();
+///     let (mut p, mut c) = rb.split();
+///
+///     p.enqueue(0).unwrap();
+///     assert_eq!(c.dequeue(), Some(0));
+/// }
+/// ```
+#[macro_export]
+macro_rules! singleton {
+    (: $ty:ty = $expr:expr) => {{
+        static TAKEN: $crate::__export::AtomicBool = $crate::__export::AtomicBool::new(false);
+        static mut VAR: $crate::__export::MaybeUninit<$ty> =
+            $crate::__export::MaybeUninit::uninit();
+
+        // NOTE(Relaxed) the swap is the only thing that needs to be atomic: exactly one caller
+        // sees `false`, and `VAR` is only ever accessed by that caller
+        if TAKEN.swap(true, $crate::__export::Ordering::Relaxed) {
+            None
+        } else {
+            let value: $ty = $expr;
+
+            // NOTE(unsafe) `TAKEN` guarantees this is the only reference to `VAR` ever created
+            let var: &'static mut $ty =
+                unsafe { (*$crate::__export::addr_of_mut!(VAR)).write(value) };
+
+            Some(var)
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::vec::Vec as StdVec;
+
+    use {RingBuffer, Vec};
+
+    #[test]
+    fn once() {
+        fn take() -> Option<&'static mut Vec<u8, 4>> {
+            singleton!(: Vec<u8, 4> = Vec::new())
+        }
+
+        let v = take().unwrap();
+        v.push(0).unwrap();
+
+        assert!(take().is_none());
+        assert!(take().is_none());
+        assert_eq!(*v, [0]);
+    }
+
+    #[test]
+    fn lazy() {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        fn take() -> Option<&'static mut usize> {
+            singleton!(: usize = COUNT.fetch_add(1, Ordering::SeqCst))
+        }
+
+        assert_eq!(take().map(|x| *x), Some(0));
+        assert_eq!(take(), None);
+
+        // the initializer is only evaluated by the first call
+        assert_eq!(COUNT.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn threads() {
+        fn take() -> Option<&'static mut RingBuffer<u8, 4>> {
+            singleton!(: RingBuffer<u8, 4> = RingBuffer::new())
+        }
+
+        let threads = (0..4)
+            .map(|_| thread::spawn(|| take().is_some()))
+            .collect::<StdVec<_>>();
+
+        let taken = threads
+            .into_iter()
+            .map(|t| t.join().unwrap())
+            .filter(|&taken| taken)
+            .count();
+
+        assert_eq!(taken, 1);
+        assert!(take().is_none());
+    }
+}
@@ -0,0 +1,383 @@
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::str::{self, FromStr, Utf8Error};
+use core::{fmt, ops};
+
+use {Error, Vec};
+
+/// [`String`] backed by a fixed size array
+///
+/// The string is guaranteed to contain valid UTF-8. Its capacity, `N`, is measured in bytes.
+///
+/// [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+pub struct String<const N: usize> {
+    vec: Vec<u8, N>,
+}
+
+impl<const N: usize> String<N> {
+    /// Constructs a new, empty string with a capacity of `N` bytes
+    pub const fn new() -> Self {
+        String { vec: Vec::new() }
+    }
+
+    /// Converts a vector of bytes into a string
+    ///
+    /// Returns an error if the bytes are not valid UTF-8
+    pub fn from_utf8(vec: Vec<u8, N>) -> Result<Self, Utf8Error> {
+        str::from_utf8(&vec)?;
+        Ok(String { vec })
+    }
+
+    /// Converts a vector of bytes into a string without checking that it contains valid UTF-8
+    ///
+    /// # Safety
+    ///
+    /// The bytes must be valid UTF-8
+    pub unsafe fn from_utf8_unchecked(vec: Vec<u8, N>) -> Self {
+        String { vec }
+    }
+
+    /// Converts the string into a vector of bytes
+    pub fn into_bytes(self) -> Vec<u8, N> {
+        self.vec
+    }
+
+    /// Extracts a string slice containing the entire string
+    pub fn as_str(&self) -> &str {
+        // NOTE(unsafe) the contents are always valid UTF-8
+        unsafe { str::from_utf8_unchecked(&self.vec) }
+    }
+
+    /// Extracts a mutable string slice containing the entire string
+    pub fn as_mut_str(&mut self) -> &mut str {
+        // NOTE(unsafe) the contents are always valid UTF-8
+        unsafe { str::from_utf8_unchecked_mut(&mut self.vec) }
+    }
+
+    /// Returns a mutable reference to the contents of the string
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the contents are valid UTF-8 when the borrow ends
+    pub unsafe fn as_mut_vec(&mut self) -> &mut Vec<u8, N> {
+        &mut self.vec
+    }
+
+    /// Returns the maximum number of bytes the string can hold
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Appends the given `char` to the end of the string
+    ///
+    /// Returns an error, leaving the string unmodified, if the character doesn't fit
+    pub fn push(&mut self, c: char) -> Result<(), Error> {
+        self.push_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Appends a string slice to the end of the string
+    ///
+    /// Returns an error, leaving the string unmodified, if the string slice doesn't fit
+    pub fn push_str(&mut self, string: &str) -> Result<(), Error> {
+        self.vec.extend_from_slice(string.as_bytes())
+    }
+
+    /// Removes the last character from the string and returns it
+    ///
+    /// Returns `None` if the string is empty
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.chars().next_back()?;
+        let new_len = self.len() - c.len_utf8();
+        self.vec.truncate(new_len);
+        Some(c)
+    }
+
+    /// Shortens the string to the specified length, in bytes
+    ///
+    /// This has no effect if `new_len` is greater than the string's current length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` does not lie on a `char` boundary
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len <= self.len() {
+            assert!(
+                self.is_char_boundary(new_len),
+                "new_len (is {}) does not lie on a char boundary",
+                new_len
+            );
+            self.vec.truncate(new_len);
+        }
+    }
+
+    /// Truncates the string, removing all its contents
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+}
+
+impl<const N: usize> Clone for String<N> {
+    fn clone(&self) -> Self {
+        String {
+            vec: self.vec.clone(),
+        }
+    }
+}
+
+impl<const N: usize> Default for String<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// # Panics
+///
+/// Panics if the string slice doesn't fit in the string. Use `str::parse` (`FromStr`) to handle
+/// the overflow instead
+impl<'a, const N: usize> From<&'a str> for String<N> {
+    fn from(s: &'a str) -> Self {
+        match s.parse() {
+            Ok(string) => string,
+            Err(_) => panic!("String::from: capacity overflow"),
+        }
+    }
+}
+
+impl<const N: usize> FromStr for String<N> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut string = String::new();
+        string.push_str(s)?;
+        Ok(string)
+    }
+}
+
+impl<const N: usize> fmt::Write for String<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize> fmt::Debug for String<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <str as fmt::Debug>::fmt(self, f)
+    }
+}
+
+impl<const N: usize> fmt::Display for String<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <str as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl<const N: usize> ops::Deref for String<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> ops::DerefMut for String<N> {
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for String<N> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for String<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<const N1: usize, const N2: usize> PartialEq<String<N2>> for String<N1> {
+    fn eq(&self, other: &String<N2>) -> bool {
+        str::eq(self, &**other)
+    }
+}
+
+impl<const N: usize> PartialEq<str> for String<N> {
+    fn eq(&self, other: &str) -> bool {
+        str::eq(self, other)
+    }
+}
+
+impl<'a, const N: usize> PartialEq<&'a str> for String<N> {
+    fn eq(&self, other: &&'a str) -> bool {
+        str::eq(self, *other)
+    }
+}
+
+impl<const N: usize> PartialEq<String<N>> for str {
+    fn eq(&self, other: &String<N>) -> bool {
+        str::eq(self, &**other)
+    }
+}
+
+impl<const N: usize> PartialEq<String<N>> for &str {
+    fn eq(&self, other: &String<N>) -> bool {
+        str::eq(*self, &**other)
+    }
+}
+
+impl<const N: usize> Eq for String<N> {}
+
+impl<const N1: usize, const N2: usize> PartialOrd<String<N2>> for String<N1> {
+    fn partial_cmp(&self, other: &String<N2>) -> Option<Ordering> {
+        str::partial_cmp(self, &**other)
+    }
+}
+
+impl<const N: usize> Ord for String<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        str::cmp(self, other)
+    }
+}
+
+impl<const N: usize> Hash for String<N> {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        str::hash(self, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+    use std;
+
+    use {Error, String, Vec};
+
+    #[test]
+    fn debug() {
+        let s: String<8> = String::from("ab\"c");
+
+        assert_eq!(std::format!("{:?}", s), "\"ab\\\"c\"");
+        assert_eq!(std::format!("{}", s), "ab\"c");
+    }
+
+    #[test]
+    fn eq() {
+        let a: String<8> = String::from("abc");
+        let b: String<4> = String::from("abc");
+        let c: String<4> = String::from("abd");
+
+        assert_eq!(a, b);
+        assert_eq!(a, "abc");
+        assert_eq!("abc", a);
+        assert_ne!(a, "ab");
+        assert!(a < c);
+    }
+
+    #[test]
+    fn from_str() {
+        let s: String<4> = "abcd".parse().unwrap();
+
+        assert_eq!(s, "abcd");
+        assert_eq!("abcde".parse::<String<4>>(), Err(Error::Full));
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_overflow() {
+        let _: String<4> = String::from("abcde");
+    }
+
+    #[test]
+    fn from_utf8() {
+        let v: Vec<u8, 8> = Vec::from_slice(b"h\xc3\xa9").unwrap();
+        let s = String::from_utf8(v).unwrap();
+
+        assert_eq!(s, "h\u{e9}");
+
+        let v: Vec<u8, 8> = Vec::from_slice(b"h\xc3").unwrap();
+
+        assert!(String::from_utf8(v).is_err());
+    }
+
+    #[test]
+    fn pop() {
+        let mut s: String<8> = String::from("a\u{e9}\u{2603}");
+
+        assert_eq!(s.pop(), Some('\u{2603}'));
+        assert_eq!(s.pop(), Some('\u{e9}'));
+        assert_eq!(s.pop(), Some('a'));
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    fn push() {
+        let mut s: String<4> = String::new();
+
+        s.push('a').unwrap();
+        s.push('\u{e9}').unwrap();
+
+        assert_eq!(s.push('\u{2603}'), Err(Error::Full));
+        assert_eq!(s, "a\u{e9}");
+
+        s.push('b').unwrap();
+
+        assert_eq!(s, "a\u{e9}b");
+        assert_eq!(s.len(), 4);
+    }
+
+    #[test]
+    fn push_str() {
+        let mut s: String<8> = String::new();
+
+        s.push_str("hello").unwrap();
+
+        assert_eq!(s.push_str(", world"), Err(Error::Full));
+        assert_eq!(s, "hello");
+
+        s.push_str("!").unwrap();
+
+        assert_eq!(s.as_str(), "hello!");
+        assert_eq!(s.as_bytes(), b"hello!");
+    }
+
+    #[test]
+    fn truncate() {
+        let mut s: String<8> = String::from("a\u{e9}b");
+
+        s.truncate(8);
+        assert_eq!(s, "a\u{e9}b");
+
+        s.truncate(3);
+        assert_eq!(s, "a\u{e9}");
+
+        s.clear();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn truncate_char_boundary() {
+        let mut s: String<8> = String::from("a\u{e9}");
+
+        s.truncate(2);
+    }
+
+    #[test]
+    fn write() {
+        let mut s: String<16> = String::new();
+
+        write!(s, "{} + {} = {}", 1, 2, 1 + 2).unwrap();
+        assert_eq!(s, "1 + 2 = 3");
+
+        assert!(write!(s, "{:>10}", 0).is_err());
+    }
+}
@@ -1,89 +1,576 @@
-use core::marker::{PhantomData, Unsize};
-use core::{ops, ptr, slice};
-
-use untagged_option::UntaggedOption;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::iter::{FromIterator, FusedIterator};
+use core::mem::MaybeUninit;
+use core::ops::{Bound, RangeBounds};
+use core::{fmt, ops, ptr, slice};
 
 use Error;
 
 /// [`Vec`] backed by a fixed size array
 ///
 /// [`Vec`]: https://doc.rust-lang.org/std/vec/struct.Vec.html
-pub struct Vec<T, A>
-where
-    // FIXME(rust-lang/rust#44580) use "const generics" instead of `Unsize`
-    A: Unsize<[T]>,
-{
-    _marker: PhantomData<[T]>,
-    buffer: UntaggedOption<A>,
+pub struct Vec<T, const N: usize> {
+    buffer: MaybeUninit<[T; N]>,
     len: usize,
 }
 
-impl<T, A> Vec<T, A>
-where
-    A: Unsize<[T]>,
-{
+impl<T, const N: usize> Vec<T, N> {
+    /// Constructs a new, empty vector with a capacity of `N`
     pub const fn new() -> Self {
         Vec {
-            _marker: PhantomData,
-            buffer: UntaggedOption::none(),
+            buffer: MaybeUninit::uninit(),
             len: 0,
         }
     }
 
-    pub fn capacity(&self) -> usize {
-        let buffer: &[T] = unsafe { self.buffer.as_ref() };
-        buffer.len()
+    /// Constructs a new vector from the contents of a slice
+    ///
+    /// Returns an error if the slice doesn't fit in the vector
+    pub fn from_slice(other: &[T]) -> Result<Self, Error>
+    where
+        T: Clone,
+    {
+        let mut v = Vec::new();
+        v.extend_from_slice(other)?;
+        Ok(v)
+    }
+
+    /// Returns the maximum number of elements the vector can hold
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Clones and appends all the elements in `other` to the vector
+    ///
+    /// Returns an error, leaving the vector unmodified, if the elements don't fit
+    pub fn extend_from_slice(&mut self, other: &[T]) -> Result<(), Error>
+    where
+        T: Clone,
+    {
+        if other.len() > N - self.len {
+            return Err(Error::Full);
+        }
+
+        for item in other {
+            // NOTE(ptr::write) the slot is uninitialized and, per the check above, within capacity
+            unsafe { ptr::write(self.buffer_mut_ptr().add(self.len), item.clone()) }
+            self.len += 1;
+        }
+
+        Ok(())
     }
 
-    pub fn iter(&self) -> slice::Iter<T> {
+    /// Appends the items yielded by `iter` to the vector
+    ///
+    /// Stops at, and returns back, the first item that doesn't fit in the vector. The items that
+    /// were appended before that one are kept.
+    pub fn try_extend<I>(&mut self, iter: I) -> Result<(), T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push(item)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, T> {
         (**self).iter()
     }
 
-    pub fn iter_mut(&mut self) -> slice::IterMut<T> {
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
         (**self).iter_mut()
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        let buffer: &[T] = unsafe { self.buffer.as_ref() };
-
         if self.len != 0 {
             self.len -= 1;
-            let item = unsafe { ptr::read(&buffer[self.len]) };
+            let item = unsafe { ptr::read(self.buffer_ptr().add(self.len)) };
             Some(item)
         } else {
             None
         }
     }
 
-    pub fn push(&mut self, item: T) -> Result<(), Error> {
-        let capacity = self.capacity();
-        let buffer: &mut [T] = unsafe { self.buffer.as_mut() };
-
-        if self.len < capacity {
+    /// Appends an `item` to the back of the collection
+    ///
+    /// Returns back the `item` if the vector is full
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        if self.len < N {
             // NOTE(ptr::write) the memory slot that we are about to write to is uninitialized. We
             // use `ptr::write` to avoid running `T`'s destructor on the uninitialized memory
-            unsafe { ptr::write(&mut buffer[self.len], item) }
+            unsafe { ptr::write(self.buffer_mut_ptr().add(self.len), item) }
             self.len += 1;
             Ok(())
         } else {
-            Err(Error::Full)
+            Err(item)
+        }
+    }
+
+    /// Inserts an `element` at position `index` within the vector, shifting all the elements
+    /// after it to the right
+    ///
+    /// Returns back the `element` if the vector is full
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the vector's length
+    pub fn insert(&mut self, index: usize, element: T) -> Result<(), T> {
+        let len = self.len;
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+
+        if len == N {
+            return Err(element);
+        }
+
+        unsafe {
+            let p = self.buffer_mut_ptr().add(index);
+            ptr::copy(p, p.add(1), len - index);
+            ptr::write(p, element);
+        }
+        self.len = len + 1;
+        Ok(())
+    }
+
+    /// Removes and returns the element at position `index` within the vector, shifting all the
+    /// elements after it to the left
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        assert!(
+            index < len,
+            "removal index (is {}) should be < len (is {})",
+            index,
+            len
+        );
+
+        unsafe {
+            let p = self.buffer_mut_ptr().add(index);
+            let item = ptr::read(p);
+            ptr::copy(p.add(1), p, len - index - 1);
+            self.len = len - 1;
+            item
+        }
+    }
+
+    /// Removes and returns the element at position `index` within the vector, replacing it with
+    /// the last element of the vector
+    ///
+    /// This does not preserve ordering, but is O(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        assert!(
+            index < len,
+            "swap_remove index (is {}) should be < len (is {})",
+            index,
+            len
+        );
+
+        unsafe {
+            let p = self.buffer_mut_ptr();
+            let item = ptr::read(p.add(index));
+            ptr::copy(p.add(len - 1), p.add(index), 1);
+            self.len = len - 1;
+            item
+        }
+    }
+
+    /// Shortens the vector, keeping the first `len` elements and dropping the rest
+    ///
+    /// This has no effect if `len` is greater than the vector's current length.
+    pub fn truncate(&mut self, len: usize) {
+        let old_len = self.len;
+
+        if len < old_len {
+            // NOTE(len) update the length first so a panicking destructor can't cause a double
+            // drop; at worst the remaining elements are leaked
+            self.len = len;
+            unsafe {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                    self.buffer_mut_ptr().add(len),
+                    old_len - len,
+                ))
+            }
+        }
+    }
+
+    /// Clears the vector, removing all values
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Resizes the vector in-place so that its length is equal to `new_len`
+    ///
+    /// If `new_len` is greater than the current length, the vector is extended by the difference,
+    /// with each additional slot filled with `value`. If `new_len` is less than the current
+    /// length, the vector is simply truncated.
+    ///
+    /// Returns an error, leaving the vector unmodified, if `new_len` is greater than the
+    /// capacity
+    pub fn resize(&mut self, new_len: usize, value: T) -> Result<(), Error>
+    where
+        T: Clone,
+    {
+        self.resize_with(new_len, || value.clone())
+    }
+
+    /// Resizes the vector in-place so that its length is equal to `new_len`
+    ///
+    /// If `new_len` is greater than the current length, the vector is extended by the difference,
+    /// with each additional slot filled with `T::default()`. If `new_len` is less than the
+    /// current length, the vector is simply truncated.
+    ///
+    /// Returns an error, leaving the vector unmodified, if `new_len` is greater than the
+    /// capacity
+    pub fn resize_default(&mut self, new_len: usize) -> Result<(), Error>
+    where
+        T: Default,
+    {
+        self.resize_with(new_len, T::default)
+    }
+
+    /// Resizes the vector in-place so that its length is equal to `new_len`
+    ///
+    /// If `new_len` is greater than the current length, the vector is extended by the difference,
+    /// with each additional slot filled with the result of calling the closure `f`. If `new_len`
+    /// is less than the current length, the vector is simply truncated.
+    ///
+    /// Returns an error, leaving the vector unmodified, if `new_len` is greater than the
+    /// capacity
+    pub fn resize_with<F>(&mut self, new_len: usize, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut() -> T,
+    {
+        if new_len > N {
+            return Err(Error::Full);
+        }
+
+        if new_len > self.len {
+            while self.len < new_len {
+                // NOTE(ptr::write) the slot is uninitialized and within capacity
+                unsafe { ptr::write(self.buffer_mut_ptr().add(self.len), f()) }
+                self.len += 1;
+            }
+        } else {
+            self.truncate(new_len);
+        }
+
+        Ok(())
+    }
+
+    /// Retains only the elements specified by the predicate `f`
+    ///
+    /// In other words, removes all the elements `e` for which `f(&e)` returns `false`. This
+    /// method operates in place, visiting each element exactly once in the original order, and
+    /// preserves the order of the retained elements.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|item| f(item))
+    }
+
+    /// Retains only the elements specified by the predicate `f`, passing a mutable reference to
+    /// each element
+    ///
+    /// In other words, removes all the elements `e` for which `f(&mut e)` returns `false`. This
+    /// method operates in place, visiting each element exactly once in the original order, and
+    /// preserves the order of the retained elements.
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let len = self.len;
+        let p = self.buffer_mut_ptr();
+
+        // NOTE(len) if `f` or a destructor panics the elements are leaked rather than dropped
+        // twice
+        self.len = 0;
+
+        let mut deleted = 0;
+        for i in 0..len {
+            unsafe {
+                let cur = p.add(i);
+                if !f(&mut *cur) {
+                    deleted += 1;
+                    ptr::drop_in_place(cur);
+                } else if deleted > 0 {
+                    ptr::copy_nonoverlapping(cur, p.add(i - deleted), 1);
+                }
+            }
         }
+
+        self.len = len - deleted;
+    }
+
+    /// Removes consecutive repeated elements in the vector
+    ///
+    /// If the vector is sorted, this removes all duplicates.
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+
+    /// Removes all but the first of consecutive elements in the vector that resolve to the same
+    /// key
+    ///
+    /// If the vector is sorted, this removes all duplicates.
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    /// Removes all but the first of consecutive elements in the vector satisfying a given
+    /// equality relation
+    ///
+    /// `same_bucket` is passed references to two elements from the vector, `(a, b)`, where `a`
+    /// comes after `b`; if it returns `true` then `a` is removed.
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let len = self.len;
+        if len <= 1 {
+            return;
+        }
+
+        let p = self.buffer_mut_ptr();
+
+        // NOTE(len) if `same_bucket` or a destructor panics the elements are leaked rather than
+        // dropped twice
+        self.len = 0;
+
+        let mut write = 1;
+        for read in 1..len {
+            unsafe {
+                let cur = p.add(read);
+                if same_bucket(&mut *cur, &mut *p.add(write - 1)) {
+                    ptr::drop_in_place(cur);
+                } else {
+                    if read != write {
+                        ptr::copy_nonoverlapping(cur, p.add(write), 1);
+                    }
+                    write += 1;
+                }
+            }
+        }
+
+        self.len = write;
+    }
+
+    /// Splits the vector into two at the given index
+    ///
+    /// Returns a new vector containing the elements in the range `[at, len)`. After the call,
+    /// `self` will be left containing the elements `[0, at)`.
+    ///
+    /// Returns an error, leaving `self` unmodified, if the elements don't fit in the new vector
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is greater than the vector's length
+    pub fn split_off<const M: usize>(&mut self, at: usize) -> Result<Vec<T, M>, Error> {
+        let len = self.len;
+        assert!(
+            at <= len,
+            "`at` split index (is {}) should be <= len (is {})",
+            at,
+            len
+        );
+
+        let other_len = len - at;
+        if other_len > M {
+            return Err(Error::Full);
+        }
+
+        let mut other = Vec::new();
+        unsafe {
+            ptr::copy_nonoverlapping(
+                self.buffer_ptr().add(at),
+                other.buffer_mut_ptr(),
+                other_len,
+            );
+        }
+        other.len = other_len;
+        self.len = at;
+
+        Ok(other)
+    }
+
+    /// Removes the specified range from the vector, returning all the removed elements as an
+    /// iterator
+    ///
+    /// The elements that follow the range are shifted to close the gap when the iterator is
+    /// dropped. If the iterator is dropped before being fully consumed, the remaining removed
+    /// elements are dropped as well.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point, if the end point is greater
+    /// than the length of the vector, or if a bound overflows `usize`
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T, N>
+    where
+        R: RangeBounds<usize>,
+    {
+        let len = self.len;
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n
+                .checked_add(1)
+                .expect("attempted to drain from after maximum usize"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n
+                .checked_add(1)
+                .expect("attempted to drain up to maximum usize"),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => len,
+        };
+        assert!(
+            start <= end,
+            "drain start index (is {}) should be <= end index (is {})",
+            start,
+            end
+        );
+        assert!(
+            end <= len,
+            "drain end index (is {}) should be <= len (is {})",
+            end,
+            len
+        );
+
+        // NOTE(len) if the `Drain` is leaked (e.g. `mem::forget`) the drained elements and the
+        // tail are leaked too, instead of being left in the vector in a moved-out state
+        self.len = start;
+
+        Drain {
+            vec: self,
+            next: start,
+            end,
+            tail_start: end,
+            tail_len: len - end,
+        }
+    }
+
+    fn buffer_ptr(&self) -> *const T {
+        self.buffer.as_ptr() as *const T
+    }
+
+    fn buffer_mut_ptr(&mut self) -> *mut T {
+        self.buffer.as_mut_ptr() as *mut T
     }
 }
 
-impl<T, A> Drop for Vec<T, A>
+impl<T, const N: usize> Clone for Vec<T, N>
 where
-    A: Unsize<[T]>,
+    T: Clone,
 {
+    fn clone(&self) -> Self {
+        let mut v: Self = Vec::new();
+        for item in self {
+            // NOTE(unsafe) `self` and `v` have the same capacity
+            unsafe { ptr::write(v.buffer_mut_ptr().add(v.len), item.clone()) }
+            v.len += 1;
+        }
+        v
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Vec<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <[T] as fmt::Debug>::fmt(self, f)
+    }
+}
+
+impl<T, const N: usize> Default for Vec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for Vec<T, N> {
     fn drop(&mut self) {
         unsafe { ptr::drop_in_place(&mut self[..]) }
     }
 }
 
-impl<'a, T, A> IntoIterator for &'a Vec<T, A>
+/// # Panics
+///
+/// Panics if the items don't fit in the vector. Use `try_extend` to handle the overflow instead
+impl<T, const N: usize> Extend<T> for Vec<T, N> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            if self.push(item).is_err() {
+                panic!("Vec::extend: capacity overflow");
+            }
+        }
+    }
+}
+
+/// # Panics
+///
+/// Panics if the items don't fit in the vector. Use `try_extend` to handle the overflow instead
+impl<'a, T, const N: usize> Extend<&'a T> for Vec<T, N>
 where
-    A: Unsize<[T]>,
+    T: 'a + Copy,
 {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = &'a T>,
+    {
+        self.extend(iter.into_iter().cloned())
+    }
+}
+
+/// # Panics
+///
+/// Panics if the iterator yields more than `N` items
+impl<T, const N: usize> FromIterator<T> for Vec<T, N> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut v = Vec::new();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<T, const N: usize> IntoIterator for Vec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { vec: self, next: 0 }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a Vec<T, N> {
     type Item = &'a T;
     type IntoIter = slice::Iter<'a, T>;
 
@@ -92,10 +579,7 @@ where
     }
 }
 
-impl<'a, T, A> IntoIterator for &'a mut Vec<T, A>
-where
-    A: Unsize<[T]>,
-{
+impl<'a, T, const N: usize> IntoIterator for &'a mut Vec<T, N> {
     type Item = &'a mut T;
     type IntoIter = slice::IterMut<'a, T>;
 
@@ -104,35 +588,256 @@ where
     }
 }
 
-impl<T, A> ops::Deref for Vec<T, A>
+impl<A, B, const N1: usize, const N2: usize> PartialEq<Vec<B, N2>> for Vec<A, N1>
 where
-    A: Unsize<[T]>,
+    A: PartialEq<B>,
 {
-    type Target = [T];
+    fn eq(&self, other: &Vec<B, N2>) -> bool {
+        <[A]>::eq(self, &**other)
+    }
+}
 
-    fn deref(&self) -> &[T] {
-        let buffer: &[T] = unsafe { self.buffer.as_ref() };
-        &buffer[..self.len]
+impl<A, B, const N: usize> PartialEq<[B]> for Vec<A, N>
+where
+    A: PartialEq<B>,
+{
+    fn eq(&self, other: &[B]) -> bool {
+        <[A]>::eq(self, other)
     }
 }
 
-impl<T, A> ops::DerefMut for Vec<T, A>
+impl<'a, A, B, const N: usize> PartialEq<&'a [B]> for Vec<A, N>
 where
-    A: Unsize<[T]>,
+    A: PartialEq<B>,
 {
+    fn eq(&self, other: &&'a [B]) -> bool {
+        <[A]>::eq(self, *other)
+    }
+}
+
+impl<'a, A, B, const N: usize> PartialEq<&'a mut [B]> for Vec<A, N>
+where
+    A: PartialEq<B>,
+{
+    fn eq(&self, other: &&'a mut [B]) -> bool {
+        <[A]>::eq(self, &**other)
+    }
+}
+
+impl<A, B, const N: usize, const M: usize> PartialEq<[B; M]> for Vec<A, N>
+where
+    A: PartialEq<B>,
+{
+    fn eq(&self, other: &[B; M]) -> bool {
+        <[A]>::eq(self, other)
+    }
+}
+
+impl<'a, A, B, const N: usize, const M: usize> PartialEq<&'a [B; M]> for Vec<A, N>
+where
+    A: PartialEq<B>,
+{
+    fn eq(&self, other: &&'a [B; M]) -> bool {
+        <[A]>::eq(self, *other)
+    }
+}
+
+impl<A, B, const N: usize> PartialEq<Vec<B, N>> for [A]
+where
+    A: PartialEq<B>,
+{
+    fn eq(&self, other: &Vec<B, N>) -> bool {
+        <[A]>::eq(self, &**other)
+    }
+}
+
+impl<A, B, const N: usize> PartialEq<Vec<B, N>> for &[A]
+where
+    A: PartialEq<B>,
+{
+    fn eq(&self, other: &Vec<B, N>) -> bool {
+        <[A]>::eq(*self, &**other)
+    }
+}
+
+impl<T, const N: usize> Eq for Vec<T, N> where T: Eq {}
+
+impl<T, const N1: usize, const N2: usize> PartialOrd<Vec<T, N2>> for Vec<T, N1>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Vec<T, N2>) -> Option<Ordering> {
+        <[T]>::partial_cmp(self, &**other)
+    }
+}
+
+impl<T, const N: usize> Ord for Vec<T, N>
+where
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        <[T]>::cmp(self, other)
+    }
+}
+
+impl<T, const N: usize> Hash for Vec<T, N>
+where
+    T: Hash,
+{
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        <[T]>::hash(self, state)
+    }
+}
+
+impl<T, const N: usize> ops::Deref for Vec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.buffer_ptr(), self.len) }
+    }
+}
+
+impl<T, const N: usize> ops::DerefMut for Vec<T, N> {
     fn deref_mut(&mut self) -> &mut [T] {
-        let len = self.len();
-        let buffer: &mut [T] = unsafe { self.buffer.as_mut() };
-        &mut buffer[..len]
+        unsafe { slice::from_raw_parts_mut(self.buffer_mut_ptr(), self.len) }
+    }
+}
+
+/// An iterator that moves out of a vector
+///
+/// This struct is created by the `into_iter` method on `Vec`
+pub struct IntoIter<T, const N: usize> {
+    // NOTE the elements in the range `next..vec.len` are the ones that have not been yielded yet
+    vec: Vec<T, N>,
+    next: usize,
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.next < self.vec.len {
+            let item = unsafe { ptr::read(self.vec.buffer_ptr().add(self.next)) };
+            self.next += 1;
+            Some(item)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.vec.len - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.next < self.vec.len {
+            self.vec.len -= 1;
+            Some(unsafe { ptr::read(self.vec.buffer_ptr().add(self.vec.len)) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        let len = self.vec.len;
+        // NOTE(len) the yielded elements must not be dropped again by the `Vec` destructor
+        self.vec.len = 0;
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                self.vec.buffer_mut_ptr().add(self.next),
+                len - self.next,
+            ))
+        }
+    }
+}
+
+/// A draining iterator for `Vec`
+///
+/// This struct is created by the `drain` method on `Vec`
+pub struct Drain<'a, T, const N: usize> {
+    vec: &'a mut Vec<T, N>,
+    // the elements in the range `next..end` are the ones that have not been yielded yet
+    next: usize,
+    end: usize,
+    // the elements that follow the drained range
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<'a, T, const N: usize> Iterator for Drain<'a, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.next < self.end {
+            let item = unsafe { ptr::read(self.vec.buffer_ptr().add(self.next)) };
+            self.next += 1;
+            Some(item)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T, const N: usize> DoubleEndedIterator for Drain<'a, T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.next < self.end {
+            self.end -= 1;
+            Some(unsafe { ptr::read(self.vec.buffer_ptr().add(self.end)) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, const N: usize> ExactSizeIterator for Drain<'a, T, N> {}
+
+impl<'a, T, const N: usize> FusedIterator for Drain<'a, T, N> {}
+
+impl<'a, T, const N: usize> Drop for Drain<'a, T, N> {
+    fn drop(&mut self) {
+        let p = self.vec.buffer_mut_ptr();
+
+        // drop the elements that were not yielded
+        let (next, end) = (self.next, self.end);
+        self.next = end;
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(p.add(next), end - next)) }
+
+        // move the tail back to close the gap
+        let start = self.vec.len;
+        if self.tail_start != start {
+            unsafe { ptr::copy(p.add(self.tail_start), p.add(start), self.tail_len) }
+        }
+        self.vec.len = start + self.tail_len;
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use Vec;
+    use core::cmp::Ordering;
+    use std;
+
+    use {Error, Vec};
 
     #[test]
     fn drop() {
+        #[derive(Debug)]
         struct Droppable;
         impl Droppable {
             fn new() -> Self {
@@ -154,7 +859,7 @@ mod tests {
 
 
         {
-            let mut v: Vec<Droppable, [Droppable; 2]> = Vec::new();
+            let mut v: Vec<Droppable, 2> = Vec::new();
             v.push(Droppable::new()).unwrap();
             v.push(Droppable::new()).unwrap();
             v.pop().unwrap();
@@ -163,29 +868,311 @@ mod tests {
         assert_eq!(unsafe { COUNT }, 0);
 
         {
-            let mut v: Vec<Droppable, [Droppable; 2]> = Vec::new();
+            let mut v: Vec<Droppable, 2> = Vec::new();
             v.push(Droppable::new()).unwrap();
             v.push(Droppable::new()).unwrap();
         }
 
         assert_eq!(unsafe { COUNT }, 0);
+
+        {
+            let mut v: Vec<Droppable, 4> = Vec::new();
+            v.resize_with(4, Droppable::new).unwrap();
+            v.truncate(3);
+            assert_eq!(unsafe { COUNT }, 3);
+            v.remove(0);
+            v.swap_remove(0);
+            assert_eq!(unsafe { COUNT }, 1);
+            v.clear();
+            assert_eq!(unsafe { COUNT }, 0);
+        }
+
+        assert_eq!(unsafe { COUNT }, 0);
+
+        {
+            let mut v: Vec<Droppable, 4> = Vec::new();
+            v.resize_with(4, Droppable::new).unwrap();
+            let mut keep = false;
+            v.retain(|_| {
+                keep = !keep;
+                keep
+            });
+            assert_eq!(unsafe { COUNT }, 2);
+            v.dedup_by(|_, _| true);
+            assert_eq!(unsafe { COUNT }, 1);
+            let w: Vec<Droppable, 4> = v.split_off(0).unwrap();
+            assert_eq!(w.len(), 1);
+        }
+
+        assert_eq!(unsafe { COUNT }, 0);
+
+        {
+            let mut v: Vec<Droppable, 4> = Vec::new();
+            v.resize_with(4, Droppable::new).unwrap();
+            let mut items = v.into_iter();
+            items.next().unwrap();
+            items.next_back().unwrap();
+            assert_eq!(unsafe { COUNT }, 2);
+        }
+
+        assert_eq!(unsafe { COUNT }, 0);
+
+        {
+            let mut v: Vec<Droppable, 4> = Vec::new();
+            v.resize_with(4, Droppable::new).unwrap();
+            v.drain(1..3).next().unwrap();
+            assert_eq!(unsafe { COUNT }, 2);
+            assert_eq!(v.len(), 2);
+        }
+
+        assert_eq!(unsafe { COUNT }, 0);
+    }
+
+    #[test]
+    fn capacity() {
+        static V: Vec<u8, 8> = Vec::new();
+
+        // the capacity is known at compile time
+        const CAPACITY: usize = V.capacity();
+
+        assert_eq!(CAPACITY, 8);
+        assert_eq!(V.capacity(), 8);
+    }
+
+    #[test]
+    fn clone() {
+        let mut v: Vec<Vec<i32, 2>, 2> = Vec::new();
+
+        v.push(Vec::from_slice(&[0, 1]).unwrap()).unwrap();
+        v.push(Vec::from_slice(&[2]).unwrap()).unwrap();
+
+        let w = v.clone();
+
+        assert_eq!(v, w);
+        assert_eq!(w[0], [0, 1]);
+        assert_eq!(w[1], [2]);
+    }
+
+    #[test]
+    fn cmp() {
+        let a: Vec<i32, 4> = Vec::from_slice(&[0, 1, 2]).unwrap();
+        let b: Vec<i32, 8> = Vec::from_slice(&[0, 1, 3]).unwrap();
+        let c: Vec<i32, 4> = Vec::from_slice(&[0, 1]).unwrap();
+
+        assert!(a < b);
+        assert!(c < a);
+        assert_eq!(a.cmp(&c), Ordering::Greater);
+        assert_eq!(a.cmp(&a.clone()), Ordering::Equal);
+    }
+
+    #[test]
+    fn debug() {
+        let v: Vec<i32, 4> = Vec::from_slice(&[0, 1, 2]).unwrap();
+
+        assert_eq!(std::format!("{:?}", v), "[0, 1, 2]");
+    }
+
+    #[test]
+    fn dedup() {
+        let mut v: Vec<i32, 8> = Vec::new();
+
+        for &x in &[1, 1, 2, 3, 3, 3, 1, 4] {
+            v.push(x).unwrap();
+        }
+
+        v.dedup();
+
+        assert_eq!(*v, [1, 2, 3, 1, 4]);
+    }
+
+    #[test]
+    fn dedup_by_key() {
+        let mut v: Vec<i32, 8> = Vec::new();
+
+        for &x in &[10, 11, 20, 21, 22, 30, 11] {
+            v.push(x).unwrap();
+        }
+
+        v.dedup_by_key(|x| *x / 10);
+
+        assert_eq!(*v, [10, 20, 30, 11]);
+    }
+
+    #[test]
+    fn drain() {
+        let mut v: Vec<i32, 8> = (0..8).collect();
+
+        {
+            let mut items = v.drain(2..6);
+            assert_eq!(items.len(), 4);
+            assert_eq!(items.next(), Some(2));
+            assert_eq!(items.next_back(), Some(5));
+        }
+        assert_eq!(*v, [0, 1, 6, 7]);
+
+        assert!(v.drain(..0).eq(None));
+        assert!(v.drain(3..).eq(Some(7)));
+        assert_eq!(*v, [0, 1, 6]);
+
+        assert!(v.drain(..).eq([0, 1, 6].iter().cloned()));
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_out_of_bounds() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.push(0).unwrap();
+
+        v.drain(0..2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_overflow() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.push(0).unwrap();
+
+        v.drain(..=usize::MAX);
+    }
+
+    #[test]
+    fn eq() {
+        let a: Vec<i32, 4> = Vec::from_slice(&[0, 1, 2]).unwrap();
+        let b: Vec<i32, 8> = Vec::from_slice(&[0, 1, 2]).unwrap();
+        let c: Vec<i32, 4> = Vec::from_slice(&[0, 1]).unwrap();
+        let s: &[i32] = &[0, 1, 2];
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a, *s);
+        assert_eq!(a, s);
+        assert_eq!(a, [0, 1, 2]);
+        assert_eq!(a, &[0, 1, 2]);
+        assert_eq!(*s, a);
+        assert_eq!(s, a);
+    }
+
+    #[test]
+    fn extend() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.extend(0..2);
+        v.extend(&[2, 3]);
+
+        assert_eq!(*v, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn extend_overflow() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.extend(0..5);
+    }
+
+    #[test]
+    fn extend_from_slice() {
+        let mut v: Vec<u8, 4> = Vec::new();
+
+        v.extend_from_slice(&[0, 1]).unwrap();
+        assert_eq!(v.extend_from_slice(&[2, 3, 4]), Err(Error::Full));
+        assert_eq!(*v, [0, 1]);
+
+        v.extend_from_slice(&[2, 3]).unwrap();
+        assert_eq!(*v, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn from_iter() {
+        let v: Vec<i32, 4> = (0..8).filter(|x| x % 2 == 0).collect();
+
+        assert_eq!(*v, [0, 2, 4, 6]);
+    }
+
+    #[test]
+    fn from_slice() {
+        let v: Vec<u8, 4> = Vec::from_slice(&[0, 1, 2]).unwrap();
+
+        assert_eq!(*v, [0, 1, 2]);
+        assert!(Vec::<u8, 2>::from_slice(&[0, 1, 2]).is_err());
     }
 
     #[test]
     fn full() {
-        let mut v: Vec<i32, [i32; 4]> = Vec::new();
+        let mut v: Vec<i32, 4> = Vec::new();
 
         v.push(0).unwrap();
         v.push(1).unwrap();
         v.push(2).unwrap();
         v.push(3).unwrap();
 
-        assert!(v.push(4).is_err());
+        assert_eq!(v.push(4), Err(4));
+    }
+
+    #[test]
+    fn hash() {
+        use core::hash::{Hash, Hasher};
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash<T: ?Sized + Hash>(t: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let a: Vec<i32, 4> = Vec::from_slice(&[0, 1, 2]).unwrap();
+        let b: Vec<i32, 8> = Vec::from_slice(&[0, 1, 2]).unwrap();
+
+        assert_eq!(hash(&a), hash(&b));
+        assert_eq!(hash(&a), hash(&[0, 1, 2][..]));
+    }
+
+    #[test]
+    fn insert() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.insert(0, 1).unwrap();
+        v.insert(0, 0).unwrap();
+        v.insert(2, 3).unwrap();
+        v.insert(2, 2).unwrap();
+
+        assert_eq!(*v, [0, 1, 2, 3]);
+        assert_eq!(v.insert(1, 4), Err(4));
+        assert_eq!(*v, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_bounds() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.push(0).unwrap();
+
+        let _ = v.insert(2, 1);
+    }
+
+    #[test]
+    fn into_iter() {
+        let v: Vec<i32, 4> = (0..4).collect();
+
+        let mut items = v.into_iter();
+
+        assert_eq!(items.len(), 4);
+        assert_eq!(items.next(), Some(0));
+        assert_eq!(items.next_back(), Some(3));
+        assert_eq!(items.len(), 2);
+        assert_eq!(items.next(), Some(1));
+        assert_eq!(items.next(), Some(2));
+        assert_eq!(items.next(), None);
+        assert_eq!(items.next_back(), None);
     }
 
     #[test]
     fn iter() {
-        let mut v: Vec<i32, [i32; 4]> = Vec::new();
+        let mut v: Vec<i32, 4> = Vec::new();
 
         v.push(0).unwrap();
         v.push(1).unwrap();
@@ -203,7 +1190,7 @@ mod tests {
 
     #[test]
     fn iter_mut() {
-        let mut v: Vec<i32, [i32; 4]> = Vec::new();
+        let mut v: Vec<i32, 4> = Vec::new();
 
         v.push(0).unwrap();
         v.push(1).unwrap();
@@ -219,9 +1206,71 @@ mod tests {
         assert_eq!(items.next(), None);
     }
 
+    #[test]
+    fn remove() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.push(0).unwrap();
+        v.push(1).unwrap();
+        v.push(2).unwrap();
+        v.push(3).unwrap();
+
+        assert_eq!(v.remove(1), 1);
+        assert_eq!(*v, [0, 2, 3]);
+        assert_eq!(v.remove(2), 3);
+        assert_eq!(*v, [0, 2]);
+        assert_eq!(v.remove(0), 0);
+        assert_eq!(*v, [2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_out_of_bounds() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.push(0).unwrap();
+
+        v.remove(1);
+    }
+
+    #[test]
+    fn resize() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.resize(3, 7).unwrap();
+        assert_eq!(*v, [7, 7, 7]);
+
+        v.resize(1, 0).unwrap();
+        assert_eq!(*v, [7]);
+
+        assert_eq!(v.resize(5, 0), Err(Error::Full));
+        assert_eq!(*v, [7]);
+
+        v.resize_default(4).unwrap();
+        assert_eq!(*v, [7, 0, 0, 0]);
+    }
+
+    #[test]
+    fn retain() {
+        let mut v: Vec<i32, 8> = Vec::new();
+
+        for x in 0..8 {
+            v.push(x).unwrap();
+        }
+
+        v.retain(|x| x % 3 != 0);
+        assert_eq!(*v, [1, 2, 4, 5, 7]);
+
+        v.retain_mut(|x| {
+            *x *= 2;
+            *x < 10
+        });
+        assert_eq!(*v, [2, 4, 8]);
+    }
+
     #[test]
     fn sanity() {
-        let mut v: Vec<i32, [i32; 4]> = Vec::new();
+        let mut v: Vec<i32, 4> = Vec::new();
 
         assert_eq!(v.pop(), None);
 
@@ -232,4 +1281,66 @@ mod tests {
         assert_eq!(v.pop(), None);
     }
 
+    #[test]
+    fn split_off() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.push(0).unwrap();
+        v.push(1).unwrap();
+        v.push(2).unwrap();
+        v.push(3).unwrap();
+
+        assert_eq!(v.split_off::<1>(1).err(), Some(Error::Full));
+        assert_eq!(*v, [0, 1, 2, 3]);
+
+        let w: Vec<i32, 2> = v.split_off(2).unwrap();
+        assert_eq!(*v, [0, 1]);
+        assert_eq!(*w, [2, 3]);
+
+        let w: Vec<i32, 2> = v.split_off(2).unwrap();
+        assert_eq!(*v, [0, 1]);
+        assert!(w.is_empty());
+    }
+
+    #[test]
+    fn swap_remove() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.push(0).unwrap();
+        v.push(1).unwrap();
+        v.push(2).unwrap();
+        v.push(3).unwrap();
+
+        assert_eq!(v.swap_remove(0), 0);
+        assert_eq!(*v, [3, 1, 2]);
+        assert_eq!(v.swap_remove(2), 2);
+        assert_eq!(*v, [3, 1]);
+    }
+
+    #[test]
+    fn try_extend() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.try_extend(0..2).unwrap();
+        assert_eq!(v.try_extend(2..6), Err(4));
+        assert_eq!(*v, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn truncate() {
+        let mut v: Vec<i32, 4> = Vec::new();
+
+        v.push(0).unwrap();
+        v.push(1).unwrap();
+        v.push(2).unwrap();
+
+        v.truncate(4);
+        assert_eq!(*v, [0, 1, 2]);
+
+        v.truncate(1);
+        assert_eq!(*v, [0]);
+
+        v.clear();
+        assert!(v.is_empty());
+    }
 }